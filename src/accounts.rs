@@ -0,0 +1,167 @@
+//! Steam accounts that signed in to an installation, and the per-game folders
+//! they keep under `userdata/<account id>`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use keyvalues_parser::Vdf;
+use serde::Serialize;
+
+use crate::userdata::user_dirs;
+use crate::{vdf, Game};
+
+/// Subtracted from a 64-bit Steam ID to get the account ID naming its
+/// `userdata` folder.
+const STEAM_ID_BASE: u64 = 76_561_197_960_265_728;
+
+/// The app ID of Steam's screenshot uploader, whose cloud folder holds the
+/// screenshots of every game.
+const SCREENSHOTS_APP_ID: u32 = 760;
+
+/// A Steam account known to an installation.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Account {
+    /// The 32-bit account ID, also the name of its `userdata` folder.
+    pub account_id: u32,
+    pub steam_id: u64,
+    /// The login name; `None` for accounts with only a `userdata` folder.
+    pub account_name: Option<String>,
+    /// The name shown to friends.
+    pub persona_name: Option<String>,
+    /// Whether this was the last account to sign in.
+    pub most_recent: bool,
+}
+
+impl Account {
+    /// The persona name, falling back to the login name and the account ID.
+    pub fn display_name(&self) -> String {
+        self.persona_name
+            .clone()
+            .or_else(|| self.account_name.clone())
+            .unwrap_or_else(|| self.account_id.to_string())
+    }
+
+    /// Whether `query` is this account's ID, login or persona name, ignoring
+    /// case.
+    pub fn matches(&self, query: &str) -> bool {
+        query == self.account_id.to_string()
+            || query == self.steam_id.to_string()
+            || [&self.account_name, &self.persona_name]
+                .into_iter()
+                .flatten()
+                .any(|name| name.eq_ignore_ascii_case(query))
+    }
+}
+
+/// What a [`UserdataFolder`] holds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UserdataKind {
+    /// `userdata/<account id>/<app id>`, with Steam Cloud files in `remote`
+    /// and per-game settings.
+    Data,
+    /// `userdata/<account id>/760/remote/<app id>/screenshots`.
+    Screenshots,
+}
+
+impl UserdataKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Data => "data",
+            Self::Screenshots => "screenshots",
+        }
+    }
+}
+
+/// A folder of one game under an account's `userdata` folder.
+#[derive(Clone, Debug, Serialize)]
+pub struct UserdataFolder {
+    pub account: Account,
+    pub kind: UserdataKind,
+    pub path: PathBuf,
+}
+
+/// Finds the accounts that signed in to the Steam installation at
+/// `steam_root`, from `config/loginusers.vdf`, plus any account that only left
+/// a `userdata` folder behind. Sorted by account ID.
+pub fn find_accounts(steam_root: &Path) -> Vec<Account> {
+    let mut accounts = login_users(steam_root);
+    for (account_id, _) in user_dirs(steam_root) {
+        if !accounts
+            .iter()
+            .any(|account| account.account_id == account_id)
+        {
+            accounts.push(Account {
+                account_id,
+                steam_id: STEAM_ID_BASE + u64::from(account_id),
+                account_name: None,
+                persona_name: None,
+                most_recent: false,
+            });
+        }
+    }
+    accounts.sort_by_key(|account| account.account_id);
+    accounts
+}
+
+/// The accounts listed in `config/loginusers.vdf`.
+fn login_users(steam_root: &Path) -> Vec<Account> {
+    let Ok(text) = fs::read_to_string(steam_root.join("config").join("loginusers.vdf")) else {
+        return Vec::new();
+    };
+    let Ok(users) = Vdf::parse(&text) else {
+        return Vec::new();
+    };
+    let Some(users) = users.value.get_obj() else {
+        return Vec::new();
+    };
+    users
+        .iter()
+        .filter_map(|(steam_id, values)| {
+            let steam_id: u64 = steam_id.parse().ok()?;
+            let account_id = u32::try_from(steam_id.checked_sub(STEAM_ID_BASE)?).ok()?;
+            let user = values.first()?.get_obj()?;
+            let field = |key| Some(vdf::get(user, key)?.get_str()?.to_string());
+            Some(Account {
+                account_id,
+                steam_id,
+                account_name: field("AccountName"),
+                persona_name: field("PersonaName"),
+                most_recent: field("MostRecent").as_deref() == Some("1"),
+            })
+        })
+        .collect()
+}
+
+/// The folders `accounts` keep for `game` under `userdata`, in account order.
+/// Only folders that exist are listed.
+pub fn find_userdata(game: &Game, accounts: &[Account]) -> Vec<UserdataFolder> {
+    let mut folders = Vec::new();
+    for account in accounts {
+        let user_dir = game
+            .installation
+            .join("userdata")
+            .join(account.account_id.to_string());
+        let candidates = [
+            (UserdataKind::Data, user_dir.join(game.app_id.to_string())),
+            (
+                UserdataKind::Screenshots,
+                user_dir
+                    .join(SCREENSHOTS_APP_ID.to_string())
+                    .join("remote")
+                    .join(game.app_id.to_string())
+                    .join("screenshots"),
+            ),
+        ];
+        for (kind, path) in candidates {
+            if path.is_dir() {
+                folders.push(UserdataFolder {
+                    account: account.clone(),
+                    kind,
+                    path,
+                });
+            }
+        }
+    }
+    folders
+}
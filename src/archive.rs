@@ -0,0 +1,58 @@
+//! Tar archives of prefix folders, made and unpacked with the system `tar`.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+/// Packs `source` into the gzip-compressed tarball `archive`, keeping
+/// symlinks as links and file permissions as they are. The folder is stored
+/// under its own name.
+///
+/// The archive is written under a temporary name and renamed when complete.
+pub fn create_archive(source: &Path, archive: &Path) -> io::Result<()> {
+    let (Some(parent), Some(name)) = (source.parent(), source.file_name()) else {
+        return Err(io::Error::other(format!(
+            "cannot archive {}",
+            source.display()
+        )));
+    };
+    if let Some(dir) = archive.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut temp = archive.as_os_str().to_os_string();
+    temp.push(".part");
+
+    let status = Command::new("tar")
+        .arg("--create")
+        .arg("--gzip")
+        .arg("--file")
+        .arg(&temp)
+        .arg("--directory")
+        .arg(parent)
+        .arg(name)
+        .status()?;
+    if !status.success() {
+        let _ = fs::remove_file(&temp);
+        return Err(io::Error::other(format!("tar exited with {status}")));
+    }
+    fs::rename(&temp, archive)
+}
+
+/// Unpacks the gzip-compressed tarball `archive` into `dir`, restoring
+/// symlinks and file permissions.
+pub(crate) fn extract_archive(archive: &Path, dir: &Path) -> io::Result<()> {
+    let status = Command::new("tar")
+        .arg("--extract")
+        .arg("--gzip")
+        .arg("--preserve-permissions")
+        .arg("--file")
+        .arg(archive)
+        .arg("--directory")
+        .arg(dir)
+        .status()?;
+    if !status.success() {
+        return Err(io::Error::other(format!("tar exited with {status}")));
+    }
+    Ok(())
+}
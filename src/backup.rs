@@ -0,0 +1,143 @@
+//! Backups of Wine/Proton prefixes, kept as tarballs with a metadata file.
+//!
+//! A backup of app 1245620 made at Unix time 1700000000 is stored as
+//! `<dir>/1245620/1700000000.tar.gz` next to `<dir>/1245620/1700000000.json`.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::archive::extract_archive;
+use crate::edit::write_atomic;
+use crate::files::unix_now;
+use crate::{create_archive, Folder, Game};
+
+/// A backed up prefix and what it belonged to.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Backup {
+    pub app_id: u32,
+    pub name: String,
+    /// Compatibility tool the game was mapped to when the backup was made.
+    pub compat_tool: Option<String>,
+    /// When the backup was made, in seconds since the Unix epoch.
+    pub created: u64,
+    /// The tarball holding the `pfx` folder. Found next to the metadata file
+    /// when loading, so a moved backup folder keeps working.
+    #[serde(default)]
+    pub archive: PathBuf,
+}
+
+/// Packs the game's `pfx` folder into `dir`, keeping symlinks and permissions,
+/// and records the game's name and compatibility tool alongside it.
+pub fn back_up_prefix(game: &Game, dir: &Path) -> io::Result<Backup> {
+    let Some(pfx) = &game.prefix_path else {
+        return Err(io::Error::other(format!("{} has no prefix", game.name)));
+    };
+    let mut created = unix_now();
+    let folder = dir.join(game.app_id.to_string());
+    // Two backups in the same second get consecutive times
+    while ["tar.gz", "json"]
+        .iter()
+        .any(|extension| folder.join(format!("{created}.{extension}")).exists())
+    {
+        created += 1;
+    }
+    let backup = Backup {
+        app_id: game.app_id,
+        name: game.name.clone(),
+        compat_tool: game.compat_tool.clone(),
+        created,
+        archive: folder.join(format!("{created}.tar.gz")),
+    };
+    create_archive(pfx, &backup.archive)?;
+    let metadata = serde_json::to_string_pretty(&backup).map_err(io::Error::other)?;
+    if let Err(error) = write_atomic(&folder.join(format!("{created}.json")), metadata.as_bytes()) {
+        let _ = fs::remove_file(&backup.archive);
+        return Err(error);
+    }
+    Ok(backup)
+}
+
+/// Every backup in `dir`, newest first. Metadata files that cannot be read or
+/// whose tarball is gone are skipped.
+pub fn find_backups(dir: &Path) -> Vec<Backup> {
+    let Ok(folders) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<Backup> = folders
+        .filter_map(|entry| fs::read_dir(entry.ok()?.path()).ok())
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "json" {
+                return None;
+            }
+            let mut backup: Backup = serde_json::from_str(&fs::read_to_string(&path).ok()?).ok()?;
+            backup.archive = path.with_extension("tar.gz");
+            backup.archive.is_file().then_some(backup)
+        })
+        .collect();
+    backups.sort_by_key(|backup| std::cmp::Reverse(backup.created));
+    backups
+}
+
+/// Replaces the game's prefix with the one in `backup` and returns the
+/// restored `pfx` folder.
+///
+/// The backup is unpacked next to the prefix first and swapped in with
+/// renames, so a failed restore leaves the current prefix as it was.
+pub fn restore_backup(backup: &Backup, game: &Game) -> io::Result<PathBuf> {
+    let compat_data = compat_data_dir(game);
+    fs::create_dir_all(&compat_data)?;
+    let secs = unix_now();
+    let staging = compat_data.join(format!(".restore-{secs}"));
+    fs::create_dir(&staging)?;
+    let restored = staging.join("pfx");
+    if let Err(error) = extract_archive(&backup.archive, &staging).and_then(|()| {
+        if restored.is_dir() {
+            Ok(())
+        } else {
+            Err(io::Error::other(format!(
+                "{} does not hold a pfx folder",
+                backup.archive.display()
+            )))
+        }
+    }) {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(error);
+    }
+
+    let pfx = compat_data.join("pfx");
+    let old = compat_data.join(format!(".pfx-{secs}.old"));
+    let had_prefix = fs::symlink_metadata(&pfx).is_ok();
+    if had_prefix {
+        fs::rename(&pfx, &old)?;
+    }
+    if let Err(error) = fs::rename(&restored, &pfx) {
+        if had_prefix {
+            let _ = fs::rename(&old, &pfx);
+        }
+        let _ = fs::remove_dir_all(&staging);
+        return Err(error);
+    }
+    let _ = fs::remove_dir_all(&staging);
+    if had_prefix {
+        fs::remove_dir_all(&old)?;
+    }
+    Ok(pfx)
+}
+
+/// The `compatdata/<app_id>` folder of the game, or where Steam would create
+/// it: in the game's library, or in the installation for shortcuts.
+fn compat_data_dir(game: &Game) -> PathBuf {
+    game.folder(Folder::CompatData).unwrap_or_else(|| {
+        game.library
+            .as_ref()
+            .unwrap_or(&game.installation)
+            .join("steamapps")
+            .join("compatdata")
+            .join(game.app_id.to_string())
+    })
+}
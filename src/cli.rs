@@ -0,0 +1,619 @@
+use std::path::PathBuf;
+
+use steam_locater::{Folder, LAUNCH_PRESETS};
+
+/// Environment variable naming the Steam installation to use, like `--steam-dir`.
+pub const STEAM_DIR_ENV: &str = "STEAM_LOCATER_STEAM_DIR";
+
+pub const USAGE: &str = "\
+Usage: steam-locater [--steam-dir PATH] [COMMAND]
+
+Commands:
+  (none)                     Start the interactive browser
+  list [--format FORMAT]     Print every game; FORMAT is table (default), json or csv
+    --account, -a ACCOUNT    Only list the shortcuts of ACCOUNT, given as an account
+                             ID, login or persona name; Steam games are always listed
+  path [FOLDER] GAME         Print a folder of GAME, given as an app ID or part of its name;
+                             FOLDER is --install (default), --prefix, --drive-c,
+                             --compatdata or --shader-cache
+  config init [--force]      Write the default config file
+  config path                Print where the config file is read from
+  installs                   Print every Steam installation found on this machine
+  orphans                    List prefixes and shader caches of games that are no
+                             longer installed
+    --delete [APP_ID...]     Delete these orphans, or all of them
+    --archive [APP_ID...]    Archive these orphans, or all of them, then delete them
+    --yes, -y                Do not ask for confirmation
+    --format, -f FORMAT      table (default), json or csv
+  set-tool GAME TOOL         Map a game to a compatibility tool in Steam's config.vdf
+    --dry-run, -n            Only print the change as a diff
+    --force                  Write even though Steam is running
+  clear-shader-cache GAME    Delete the shader cache of GAME; Steam rebuilds it
+    --yes, -y                Do not ask for confirmation
+    --force                  Delete even though Steam is running
+  backup GAME                Back up the prefix of GAME to the backup folder
+  backups [GAME]             List prefix backups, of every game or only of GAME
+    --format, -f FORMAT      table (default), json or csv
+  restore GAME [CREATED]     Replace the prefix of GAME with its newest backup, or
+                             the one made at CREATED as shown by backups
+    --yes, -y                Do not ask for confirmation
+    --force                  Restore even though Steam is running
+  reset-prefix GAME          Move the compatdata folder of GAME to the trash folder,
+                             keeping drive_c/users/steamuser in a fresh prefix
+    --discard-saves          Do not keep drive_c/users/steamuser
+    --yes, -y                Do not ask for confirmation
+    --force                  Reset even though Steam is running
+  saves GAME                 List folders in the prefix of GAME that likely hold saves
+    --format, -f FORMAT      table (default), json or csv
+  backup-saves [GAME]        Copy the saves of GAME, or of every game, into a new
+                             generation in the save backup folder
+    --keep N                 Keep N generations of each game (default from config)
+  save-backups [GAME]        List save backups, of every game or only of GAME
+    --format, -f FORMAT      table (default), json or csv
+  restore-saves GAME [CREATED]
+                             Put back the newest saves of GAME, or the generation
+                             made at CREATED as shown by save-backups
+    --yes, -y                Do not ask for confirmation
+    --force                  Restore even though Steam is running
+  accounts                   List the Steam accounts that signed in to Steam
+    --format, -f FORMAT      table (default), json or csv
+  userdata GAME              List the userdata folders of GAME for every account
+    --format, -f FORMAT      table (default), json or csv
+  launch-options GAME        List the launch options each account set for GAME
+    --format, -f FORMAT      table (default), json or csv
+  set-launch-options GAME [OPTIONS]
+                             Set the launch options of GAME, e.g. 'PROTON_LOG=1
+                             %command%'; put -- before options starting with --
+    --preset, -p NAME        Switch PROTON_LOG, DXVK_HUD or gamemoderun on or off,
+                             in OPTIONS or in the current options
+    --account, -a ACCOUNT    The account to change, when several have the game
+    --dry-run, -n            Only print the change
+    --force                  Write even though Steam is running
+  help                       Show this message
+
+Options:
+  --steam-dir PATH           Use the Steam installation at PATH; also read from
+                             STEAM_LOCATER_STEAM_DIR";
+
+pub struct Cli {
+    pub steam_dir: Option<PathBuf>,
+    pub command: Command,
+}
+
+pub enum Command {
+    Tui,
+    List {
+        format: Format,
+        account: Option<String>,
+    },
+    Path {
+        folder: Folder,
+        query: String,
+    },
+    ConfigInit {
+        force: bool,
+    },
+    ConfigPath,
+    Installs,
+    Orphans {
+        format: Format,
+        action: Option<OrphanAction>,
+        app_ids: Vec<u32>,
+        yes: bool,
+    },
+    SetTool {
+        query: String,
+        tool: String,
+        dry_run: bool,
+        force: bool,
+    },
+    ClearShaderCache {
+        query: String,
+        yes: bool,
+        force: bool,
+    },
+    Backup {
+        query: String,
+    },
+    Backups {
+        query: Option<String>,
+        format: Format,
+    },
+    Restore {
+        query: String,
+        created: Option<u64>,
+        yes: bool,
+        force: bool,
+    },
+    ResetPrefix {
+        query: String,
+        keep_saves: bool,
+        yes: bool,
+        force: bool,
+    },
+    Saves {
+        query: String,
+        format: Format,
+    },
+    BackupSaves {
+        query: Option<String>,
+        keep: Option<usize>,
+    },
+    SaveBackups {
+        query: Option<String>,
+        format: Format,
+    },
+    RestoreSaves {
+        query: String,
+        created: Option<u64>,
+        yes: bool,
+        force: bool,
+    },
+    Accounts {
+        format: Format,
+    },
+    LaunchOptions {
+        query: String,
+        format: Format,
+    },
+    SetLaunchOptions {
+        query: String,
+        options: Option<String>,
+        presets: Vec<String>,
+        account: Option<String>,
+        dry_run: bool,
+        force: bool,
+    },
+    Userdata {
+        query: String,
+        format: Format,
+    },
+    Help,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OrphanAction {
+    Delete,
+    Archive,
+}
+
+#[derive(Clone, Copy)]
+pub enum Format {
+    Table,
+    Json,
+    Csv,
+}
+
+impl Format {
+    fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "table" => Ok(Self::Table),
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            other => Err(format!(
+                "unknown format '{other}', expected table, json or csv"
+            )),
+        }
+    }
+}
+
+/// Removes `--format`, `-f` and `--format=` with their values from `args`, up
+/// to a `--`, and returns the last format given or a table.
+fn take_format(args: &mut Vec<String>) -> Result<Format, String> {
+    let mut format = Format::Table;
+    let mut i = 0;
+    while i < args.len() && args[i] != "--" {
+        if args[i] == "--format" || args[i] == "-f" {
+            let value = args.get(i + 1).ok_or("--format needs a value")?;
+            format = Format::parse(value)?;
+            args.drain(i..i + 2);
+        } else if let Some(value) = args[i].strip_prefix("--format=") {
+            format = Format::parse(value)?;
+            args.remove(i);
+        } else {
+            i += 1;
+        }
+    }
+    Ok(format)
+}
+
+fn parse_keep(value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(keep) if keep > 0 => Ok(keep),
+        _ => Err(format!("--keep needs a positive number, not '{value}'")),
+    }
+}
+
+pub fn parse(args: impl Iterator<Item = String>) -> Result<Cli, String> {
+    let mut steam_dir = None;
+    let mut rest = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--steam-dir" {
+            let value = args.next().ok_or("--steam-dir needs a value")?;
+            steam_dir = Some(PathBuf::from(value));
+        } else if let Some(value) = arg.strip_prefix("--steam-dir=") {
+            steam_dir = Some(PathBuf::from(value));
+        } else if arg == "--" {
+            // Everything after `--` belongs to the command, e.g. launch options
+            rest.push(arg);
+            rest.extend(args.by_ref());
+        } else {
+            rest.push(arg);
+        }
+    }
+    let steam_dir = steam_dir.or_else(|| {
+        std::env::var_os(STEAM_DIR_ENV)
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+    });
+
+    let command = parse_command(rest.into_iter())?;
+    Ok(Cli { steam_dir, command })
+}
+
+fn parse_command(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let command = match args.next() {
+        None => return Ok(Command::Tui),
+        Some(command) => command,
+    };
+
+    match command.as_str() {
+        "list" => {
+            let mut args: Vec<String> = args.collect();
+            let format = take_format(&mut args)?;
+            let mut account = None;
+            let mut args = args.into_iter();
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--account" | "-a" => {
+                        account = Some(args.next().ok_or("--account needs a value")?);
+                    }
+                    other => return Err(format!("unexpected argument '{other}'")),
+                }
+            }
+            Ok(Command::List { format, account })
+        }
+        "path" => {
+            let mut folder = Folder::Install;
+            let mut query = None;
+            for arg in args {
+                match arg.as_str() {
+                    "--install" => folder = Folder::Install,
+                    "--prefix" => folder = Folder::Prefix,
+                    "--drive-c" => folder = Folder::DriveC,
+                    "--compatdata" => folder = Folder::CompatData,
+                    "--shader-cache" => folder = Folder::ShaderCache,
+                    other if other.starts_with("--") => {
+                        return Err(format!("unexpected argument '{other}'"))
+                    }
+                    _ if query.is_some() => return Err(format!("unexpected argument '{arg}'")),
+                    _ => query = Some(arg),
+                }
+            }
+            let query = query.ok_or("path needs an app ID or game name")?;
+            Ok(Command::Path { folder, query })
+        }
+        "config" => match args.next().as_deref() {
+            Some("init") => {
+                let mut force = false;
+                for arg in args {
+                    match arg.as_str() {
+                        "--force" => force = true,
+                        other => return Err(format!("unexpected argument '{other}'")),
+                    }
+                }
+                Ok(Command::ConfigInit { force })
+            }
+            Some("path") => Ok(Command::ConfigPath),
+            Some(other) => Err(format!("unknown config command '{other}'")),
+            None => Err("config needs a command: init or path".to_string()),
+        },
+        "installs" => Ok(Command::Installs),
+        "orphans" => {
+            let mut args: Vec<String> = args.collect();
+            let format = take_format(&mut args)?;
+            let mut action = None;
+            let mut app_ids = Vec::new();
+            let mut yes = false;
+            for arg in args {
+                match arg.as_str() {
+                    "--delete" => action = Some(OrphanAction::Delete),
+                    "--archive" => action = Some(OrphanAction::Archive),
+                    "--yes" | "-y" => yes = true,
+                    other => match other.parse() {
+                        Ok(app_id) => app_ids.push(app_id),
+                        Err(_) => return Err(format!("unexpected argument '{other}'")),
+                    },
+                }
+            }
+            if action.is_none() && !app_ids.is_empty() {
+                return Err("app IDs need --delete or --archive".to_string());
+            }
+            Ok(Command::Orphans {
+                format,
+                action,
+                app_ids,
+                yes,
+            })
+        }
+        "set-tool" => {
+            let mut dry_run = false;
+            let mut force = false;
+            let mut positional = Vec::new();
+            for arg in args {
+                match arg.as_str() {
+                    "--dry-run" | "-n" => dry_run = true,
+                    "--force" => force = true,
+                    other if other.starts_with("--") => {
+                        return Err(format!("unexpected argument '{other}'"))
+                    }
+                    _ => positional.push(arg),
+                }
+            }
+            match <[String; 2]>::try_from(positional) {
+                Ok([query, tool]) => Ok(Command::SetTool {
+                    query,
+                    tool,
+                    dry_run,
+                    force,
+                }),
+                Err(_) => Err("set-tool needs a game and a tool name".to_string()),
+            }
+        }
+        "clear-shader-cache" => {
+            let mut yes = false;
+            let mut force = false;
+            let mut query = None;
+            for arg in args {
+                match arg.as_str() {
+                    "--yes" | "-y" => yes = true,
+                    "--force" => force = true,
+                    other if other.starts_with("--") => {
+                        return Err(format!("unexpected argument '{other}'"))
+                    }
+                    _ if query.is_some() => return Err(format!("unexpected argument '{arg}'")),
+                    _ => query = Some(arg),
+                }
+            }
+            let query = query.ok_or("clear-shader-cache needs an app ID or game name")?;
+            Ok(Command::ClearShaderCache { query, yes, force })
+        }
+        "backup" => match (args.next(), args.next()) {
+            (Some(query), None) if !query.starts_with("--") => Ok(Command::Backup { query }),
+            (Some(_), Some(other)) => Err(format!("unexpected argument '{other}'")),
+            _ => Err("backup needs an app ID or game name".to_string()),
+        },
+        "backup-saves" => {
+            let mut keep = None;
+            let mut query = None;
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--keep" => {
+                        let value = args.next().ok_or("--keep needs a value")?;
+                        keep = Some(parse_keep(&value)?);
+                    }
+                    other => match other.strip_prefix("--keep=") {
+                        Some(value) => keep = Some(parse_keep(value)?),
+                        None if other.starts_with("--") || query.is_some() => {
+                            return Err(format!("unexpected argument '{other}'"))
+                        }
+                        None => query = Some(arg),
+                    },
+                }
+            }
+            Ok(Command::BackupSaves { query, keep })
+        }
+        "backups" | "save-backups" => {
+            let mut args: Vec<String> = args.collect();
+            let format = take_format(&mut args)?;
+            let mut query = None;
+            for arg in args {
+                match arg.as_str() {
+                    other if other.starts_with("--") || query.is_some() => {
+                        return Err(format!("unexpected argument '{other}'"))
+                    }
+                    _ => query = Some(arg),
+                }
+            }
+            Ok(if command == "backups" {
+                Command::Backups { query, format }
+            } else {
+                Command::SaveBackups { query, format }
+            })
+        }
+        "restore" | "restore-saves" => {
+            let mut yes = false;
+            let mut force = false;
+            let mut positional = Vec::new();
+            for arg in args {
+                match arg.as_str() {
+                    "--yes" | "-y" => yes = true,
+                    "--force" => force = true,
+                    other if other.starts_with("--") => {
+                        return Err(format!("unexpected argument '{other}'"))
+                    }
+                    _ => positional.push(arg),
+                }
+            }
+            let mut positional = positional.into_iter();
+            let query = positional
+                .next()
+                .ok_or_else(|| format!("{command} needs an app ID or game name"))?;
+            let created = match positional.next() {
+                Some(value) => Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("'{value}' is not a backup time"))?,
+                ),
+                None => None,
+            };
+            if let Some(other) = positional.next() {
+                return Err(format!("unexpected argument '{other}'"));
+            }
+            Ok(if command == "restore" {
+                Command::Restore {
+                    query,
+                    created,
+                    yes,
+                    force,
+                }
+            } else {
+                Command::RestoreSaves {
+                    query,
+                    created,
+                    yes,
+                    force,
+                }
+            })
+        }
+        "reset-prefix" => {
+            let mut keep_saves = true;
+            let mut yes = false;
+            let mut force = false;
+            let mut query = None;
+            for arg in args {
+                match arg.as_str() {
+                    "--discard-saves" => keep_saves = false,
+                    "--yes" | "-y" => yes = true,
+                    "--force" => force = true,
+                    other if other.starts_with("--") => {
+                        return Err(format!("unexpected argument '{other}'"))
+                    }
+                    _ if query.is_some() => return Err(format!("unexpected argument '{arg}'")),
+                    _ => query = Some(arg),
+                }
+            }
+            let query = query.ok_or("reset-prefix needs an app ID or game name")?;
+            Ok(Command::ResetPrefix {
+                query,
+                keep_saves,
+                yes,
+                force,
+            })
+        }
+        "accounts" => {
+            let mut args: Vec<String> = args.collect();
+            let format = take_format(&mut args)?;
+            if let Some(other) = args.first() {
+                return Err(format!("unexpected argument '{other}'"));
+            }
+            Ok(Command::Accounts { format })
+        }
+        "set-launch-options" => {
+            let mut presets = Vec::new();
+            let mut account = None;
+            let mut dry_run = false;
+            let mut force = false;
+            let mut positional = Vec::new();
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--preset" | "-p" => {
+                        let name = args.next().ok_or("--preset needs a value")?;
+                        if !LAUNCH_PRESETS.iter().any(|preset| preset.name == name) {
+                            let names: Vec<&str> =
+                                LAUNCH_PRESETS.iter().map(|preset| preset.name).collect();
+                            return Err(format!(
+                                "unknown preset '{name}'; presets: {}",
+                                names.join(", ")
+                            ));
+                        }
+                        presets.push(name);
+                    }
+                    "--account" | "-a" => {
+                        account = Some(args.next().ok_or("--account needs a value")?);
+                    }
+                    "--dry-run" | "-n" => dry_run = true,
+                    "--force" => force = true,
+                    "--" => positional.extend(args.by_ref()),
+                    other if other.starts_with("--") => {
+                        return Err(format!("unexpected argument '{other}'"))
+                    }
+                    _ => positional.push(arg),
+                }
+            }
+            let mut positional = positional.into_iter();
+            let query = positional
+                .next()
+                .ok_or("set-launch-options needs an app ID or game name")?;
+            let options = positional.next();
+            if let Some(extra) = positional.next() {
+                return Err(format!(
+                    "unexpected argument '{extra}'; quote the launch options"
+                ));
+            }
+            if options.is_none() && presets.is_empty() {
+                return Err("set-launch-options needs launch options or --preset".to_string());
+            }
+            Ok(Command::SetLaunchOptions {
+                query,
+                options,
+                presets,
+                account,
+                dry_run,
+                force,
+            })
+        }
+        "saves" | "userdata" | "launch-options" => {
+            let mut args: Vec<String> = args.collect();
+            let format = take_format(&mut args)?;
+            let mut query = None;
+            for arg in args {
+                match arg.as_str() {
+                    other if other.starts_with("--") || query.is_some() => {
+                        return Err(format!("unexpected argument '{other}'"))
+                    }
+                    _ => query = Some(arg),
+                }
+            }
+            let query = query.ok_or(format!("{command} needs an app ID or game name"))?;
+            Ok(match command.as_str() {
+                "saves" => Command::Saves { query, format },
+                "userdata" => Command::Userdata { query, format },
+                _ => Command::LaunchOptions { query, format },
+            })
+        }
+        "help" | "--help" | "-h" => Ok(Command::Help),
+        other => Err(format!("unknown command '{other}'")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> impl Iterator<Item = String> + '_ {
+        line.split_whitespace().map(str::to_string)
+    }
+
+    #[test]
+    fn takes_format_anywhere() {
+        for line in [
+            "saves -f json elden",
+            "saves elden --format json",
+            "saves --format=json elden",
+        ] {
+            let command = parse_command(args(line)).unwrap();
+            assert!(
+                matches!(&command, Command::Saves { query, format: Format::Json } if query == "elden"),
+                "{line}"
+            );
+        }
+        assert!(parse_command(args("accounts --format")).is_err());
+        assert!(parse_command(args("accounts --format xml")).is_err());
+        assert!(parse_command(args("accounts extra")).is_err());
+    }
+
+    #[test]
+    fn leaves_arguments_after_double_dash() {
+        let cli = parse(args(
+            "--steam-dir /steam set-launch-options elden -- --steam-dir=/other",
+        ))
+        .unwrap();
+        assert_eq!(cli.steam_dir, Some(PathBuf::from("/steam")));
+        assert!(matches!(
+            cli.command,
+            Command::SetLaunchOptions { options: Some(options), .. } if options == "--steam-dir=/other"
+        ));
+    }
+}
@@ -0,0 +1,47 @@
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+/// Clipboard tools tried in order: Wayland, X11 and macOS.
+const COPIERS: [(&str, &[&str]); 4] = [
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+    ("pbcopy", &[]),
+];
+
+/// Puts `text` on the clipboard, returning the program used or a message
+/// describing the failure.
+pub fn copy(text: &str) -> Result<String, String> {
+    let mut missing = Vec::new();
+    for (program, args) in COPIERS {
+        let child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                missing.push(program);
+                continue;
+            }
+            Err(error) => return Err(format!("Could not run {program}: {error}")),
+        };
+        // The tools fork to keep serving the clipboard once stdin is closed
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(text.as_bytes())
+                .map_err(|error| format!("Could not write to {program}: {error}"))?;
+        }
+        return match child.wait() {
+            Ok(status) if status.success() => Ok(program.to_string()),
+            Ok(status) => Err(format!("{program} failed ({status}).")),
+            Err(error) => Err(format!("Could not wait for {program}: {error}")),
+        };
+    }
+    Err(format!(
+        "No clipboard tool found (tried {}).",
+        missing.join(", ")
+    ))
+}
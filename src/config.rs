@@ -0,0 +1,447 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use ratatui::style::Color;
+use serde::Deserialize;
+use steam_locater::SortKey;
+
+/// Written by `config init`. Kept in sync with [`Config::default`].
+pub const DEFAULT_CONFIG: &str = r##"# steam-locater configuration
+
+# Steam installation to use instead of the detected one. --steam-dir and
+# STEAM_LOCATER_STEAM_DIR take precedence.
+# steam_root = "/home/me/.local/share/Steam"
+
+# Library folders to scan in addition to the ones Steam knows about.
+extra_libraries = []
+
+# Commands used to open folders, tried in order. `{path}` is replaced by the
+# folder; without it the folder is appended. xdg-open, gio open and open are
+# always tried last.
+opener = []
+
+# Where orphaned folders are archived before they are deleted. Defaults to
+# $XDG_DATA_HOME/steam-locater/archive.
+# archive_dir = "/home/me/steam-archive"
+
+# Where prefix backups are kept. Defaults to
+# $XDG_DATA_HOME/steam-locater/backups.
+# backup_dir = "/home/me/prefix-backups"
+
+# Where reset prefixes are moved. Defaults to $XDG_DATA_HOME/steam-locater/trash.
+# trash_dir = "/home/me/prefix-trash"
+
+# Where save backups are kept, and how many of each game's backups to keep.
+# Defaults to $XDG_DATA_HOME/steam-locater/saves.
+# save_backup_dir = "/home/me/save-backups"
+save_generations = 5
+
+# App IDs of games to leave out of the list.
+hidden_games = []
+
+# Initial sort order: default, name, app-id, size, last-played, last-updated,
+# library or kind.
+default_sort = "default"
+sort_descending = false
+
+# Colors are names like "white" or "dark-gray", hex values like "#ff8800" or
+# indexed colors like "42".
+[theme]
+text = "white"
+dim = "dark-gray"
+label = "gray"
+matched = "yellow"
+selected = "blue"
+
+# Single characters. Arrow keys, Enter and Backspace are fixed, and so are 1-9
+# for userdata folders, Space, d and a in the orphans view and c and b in the
+# saves view.
+[keys]
+quit = "q"
+search = "/"
+open_prefix = "p"
+sort = "s"
+reverse_sort = "r"
+installation = "i"
+account = "a"
+tools = "t"
+change_tool = "c"
+orphans = "o"
+clear_shader_cache = "x"
+backup_prefix = "b"
+backups = "B"
+reset_prefix = "R"
+saves = "S"
+launch_options = "L"
+"##;
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub steam_root: Option<PathBuf>,
+    pub extra_libraries: Vec<PathBuf>,
+    pub opener: Vec<String>,
+    pub archive_dir: Option<PathBuf>,
+    pub backup_dir: Option<PathBuf>,
+    pub trash_dir: Option<PathBuf>,
+    pub save_backup_dir: Option<PathBuf>,
+    /// How many save backups of each game are kept.
+    pub save_generations: usize,
+    pub hidden_games: Vec<u32>,
+    #[serde(deserialize_with = "deserialize_sort_key")]
+    pub default_sort: SortKey,
+    pub sort_descending: bool,
+    pub theme: Theme,
+    pub keys: Keys,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    #[serde(deserialize_with = "deserialize_color")]
+    pub text: Color,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub dim: Color,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub label: Color,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub matched: Color,
+    #[serde(deserialize_with = "deserialize_color")]
+    pub selected: Color,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Keys {
+    pub quit: char,
+    pub search: char,
+    pub open_prefix: char,
+    pub sort: char,
+    pub reverse_sort: char,
+    /// Cycles the installation filter.
+    pub installation: char,
+    /// Cycles the account whose shortcuts are shown.
+    pub account: char,
+    /// Switches between the games and compatibility tools views.
+    pub tools: char,
+    /// Changes the selected game's compatibility tool.
+    pub change_tool: char,
+    /// Switches between the games and orphaned folders views.
+    pub orphans: char,
+    /// Deletes the selected game's shader cache, after confirmation.
+    pub clear_shader_cache: char,
+    /// Backs up the selected game's prefix.
+    pub backup_prefix: char,
+    /// Switches between the games view and the backups of the selected game.
+    pub backups: char,
+    /// Moves the selected game's prefix to the trash, after confirmation.
+    pub reset_prefix: char,
+    /// Switches between the games view and the save folders of the selected game.
+    pub saves: char,
+    /// Edits the selected game's launch options.
+    pub launch_options: char,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            steam_root: None,
+            extra_libraries: Vec::new(),
+            opener: Vec::new(),
+            archive_dir: None,
+            backup_dir: None,
+            trash_dir: None,
+            save_backup_dir: None,
+            save_generations: 5,
+            hidden_games: Vec::new(),
+            default_sort: SortKey::Default,
+            sort_descending: false,
+            theme: Theme::default(),
+            keys: Keys::default(),
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            text: Color::White,
+            dim: Color::DarkGray,
+            label: Color::Gray,
+            matched: Color::Yellow,
+            selected: Color::Blue,
+        }
+    }
+}
+
+impl Default for Keys {
+    fn default() -> Self {
+        Self {
+            quit: 'q',
+            search: '/',
+            open_prefix: 'p',
+            sort: 's',
+            reverse_sort: 'r',
+            installation: 'i',
+            account: 'a',
+            tools: 't',
+            change_tool: 'c',
+            orphans: 'o',
+            clear_shader_cache: 'x',
+            backup_prefix: 'b',
+            backups: 'B',
+            reset_prefix: 'R',
+            saves: 'S',
+            launch_options: 'L',
+        }
+    }
+}
+
+impl Keys {
+    fn bindings(&self) -> [(&'static str, char); 16] {
+        [
+            ("quit", self.quit),
+            ("search", self.search),
+            ("open_prefix", self.open_prefix),
+            ("sort", self.sort),
+            ("reverse_sort", self.reverse_sort),
+            ("installation", self.installation),
+            ("account", self.account),
+            ("tools", self.tools),
+            ("change_tool", self.change_tool),
+            ("orphans", self.orphans),
+            ("clear_shader_cache", self.clear_shader_cache),
+            ("backup_prefix", self.backup_prefix),
+            ("backups", self.backups),
+            ("reset_prefix", self.reset_prefix),
+            ("saves", self.saves),
+            ("launch_options", self.launch_options),
+        ]
+    }
+
+    /// Refuses keys that would hide the fixed ones: '1' to '9' open userdata
+    /// folders in the games view, and the keys still read in the orphans and
+    /// saves views come before the ones these views handle themselves.
+    fn check_reserved(&self) -> Result<(), String> {
+        for (action, key) in self.bindings() {
+            if ('1'..='9').contains(&key) {
+                return Err(format!(
+                    "keys.{action} is '{key}', which opens a userdata folder"
+                ));
+            }
+        }
+        // Keys still read in a view, with the ones the view handles itself
+        let view_keys = [
+            ("orphans", " da", "quit", self.quit),
+            ("orphans", " da", "orphans", self.orphans),
+            ("orphans", " da", "tools", self.tools),
+            ("saves", "cb", "quit", self.quit),
+            ("saves", "cb", "saves", self.saves),
+        ];
+        for (view, reserved, action, key) in view_keys {
+            if reserved.contains(key) {
+                return Err(format!(
+                    "keys.{action} is '{key}', which the {view} view uses"
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Invalid(PathBuf, String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Read(path, error) => write!(f, "could not read {}: {error}", path.display()),
+            Self::Parse(path, error) => write!(f, "invalid config {}: {error}", path.display()),
+            Self::Invalid(path, message) => {
+                write!(f, "invalid config {}: {message}", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// `$XDG_CONFIG_HOME/steam-locater/config.toml`, or under `~/.config` when unset.
+pub fn config_path() -> Option<PathBuf> {
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+    Some(config_home.join("steam-locater").join("config.toml"))
+}
+
+/// `$XDG_DATA_HOME/steam-locater`, or `~/.local/share/steam-locater` when
+/// `XDG_DATA_HOME` is unset.
+pub fn data_dir() -> Option<PathBuf> {
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| Path::new(&home).join(".local").join("share"))
+        })?;
+    Some(data_home.join("steam-locater"))
+}
+
+impl Config {
+    /// Where orphaned folders are archived.
+    pub fn archive_dir(&self) -> Option<PathBuf> {
+        self.archive_dir
+            .clone()
+            .or_else(|| Some(data_dir()?.join("archive")))
+    }
+
+    /// Where prefix backups are kept.
+    pub fn backup_dir(&self) -> Option<PathBuf> {
+        self.backup_dir
+            .clone()
+            .or_else(|| Some(data_dir()?.join("backups")))
+    }
+
+    /// Where reset prefixes are moved.
+    pub fn trash_dir(&self) -> Option<PathBuf> {
+        self.trash_dir
+            .clone()
+            .or_else(|| Some(data_dir()?.join("trash")))
+    }
+
+    /// Where save backups are kept.
+    pub fn save_backup_dir(&self) -> Option<PathBuf> {
+        self.save_backup_dir
+            .clone()
+            .or_else(|| Some(data_dir()?.join("saves")))
+    }
+
+    /// Loads the config file, or the defaults when there is none.
+    pub fn load() -> Result<Self, ConfigError> {
+        match config_path() {
+            Some(path) if path.exists() => Self::load_from(&path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, ConfigError> {
+        let text = fs::read_to_string(path).map_err(|e| ConfigError::Read(path.into(), e))?;
+        let config: Self = toml::from_str(&text).map_err(|e| ConfigError::Parse(path.into(), e))?;
+        config
+            .validate()
+            .map_err(|message| ConfigError::Invalid(path.into(), message))?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if let Some(root) = &self.steam_root {
+            if !root.is_dir() {
+                return Err(format!("steam_root {} is not a folder", root.display()));
+            }
+        }
+        for library in &self.extra_libraries {
+            if !library.join("steamapps").is_dir() {
+                return Err(format!(
+                    "extra library {} has no steamapps folder",
+                    library.display()
+                ));
+            }
+        }
+        if self.opener.iter().any(|command| command.trim().is_empty()) {
+            return Err("opener commands must not be empty".to_string());
+        }
+        if self.save_generations == 0 {
+            return Err("save_generations must be at least 1".to_string());
+        }
+
+        let bindings = self.keys.bindings();
+        for (i, (action, key)) in bindings.iter().enumerate() {
+            if let Some((other, _)) = bindings[i + 1..].iter().find(|(_, other)| other == key) {
+                return Err(format!("keys.{action} and keys.{other} are both '{key}'"));
+            }
+        }
+        self.keys.check_reserved()
+    }
+}
+
+/// Writes [`DEFAULT_CONFIG`] to `path`, refusing to replace an existing file.
+pub fn init(path: &Path, force: bool) -> Result<(), String> {
+    if path.exists() && !force {
+        return Err(format!(
+            "{} already exists, use --force to overwrite it",
+            path.display()
+        ));
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|error| format!("could not create {}: {error}", parent.display()))?;
+    }
+    fs::write(path, DEFAULT_CONFIG)
+        .map_err(|error| format!("could not write {}: {error}", path.display()))
+}
+
+fn deserialize_sort_key<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<SortKey, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    SortKey::parse(&name).ok_or_else(|| {
+        let names: Vec<&str> = SortKey::ALL.iter().map(|key| key.as_str()).collect();
+        serde::de::Error::custom(format!(
+            "unknown sort '{name}', expected one of {}",
+            names.join(", ")
+        ))
+    })
+}
+
+fn deserialize_color<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    Color::from_str(&name).map_err(|_| serde::de::Error::custom(format!("unknown color '{name}'")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_config_matches_defaults() {
+        let config: Config = toml::from_str(DEFAULT_CONFIG).unwrap();
+        assert_eq!(config, Config::default());
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[test]
+    fn rejects_duplicate_keys() {
+        let config: Config = toml::from_str("[keys]\nsort = \"q\"").unwrap();
+        assert_eq!(
+            config.validate(),
+            Err("keys.quit and keys.sort are both 'q'".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_reserved_keys() {
+        let config: Config = toml::from_str("[keys]\nsearch = \"1\"").unwrap();
+        assert!(config.validate().is_err());
+        let config: Config = toml::from_str("[keys]\norphans = \"d\"").unwrap();
+        assert_eq!(
+            config.validate(),
+            Err("keys.orphans is 'd', which the orphans view uses".to_string())
+        );
+        let config: Config =
+            toml::from_str("[keys]\nsaves = \"b\"\nbackup_prefix = \"P\"").unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_colors() {
+        let error = toml::from_str::<Config>("[theme]\ntext = \"blurple\"").unwrap_err();
+        assert!(error.message().contains("unknown color 'blurple'"));
+    }
+}
@@ -0,0 +1,194 @@
+//! Safe edits of Steam's own files: preview, backup and atomic replace.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::files::unix_now;
+
+/// Lines of unchanged text shown around each change in [`FileEdit::diff`].
+const CONTEXT: usize = 3;
+
+/// A pending change to a file, made by comparing its old and new contents.
+#[derive(Clone, Debug)]
+pub struct FileEdit {
+    pub path: PathBuf,
+    pub original: String,
+    pub updated: String,
+}
+
+impl FileEdit {
+    pub fn is_unchanged(&self) -> bool {
+        self.original == self.updated
+    }
+
+    /// The change as a unified diff.
+    pub fn diff(&self) -> String {
+        let old: Vec<&str> = self.original.lines().collect();
+        let new: Vec<&str> = self.updated.lines().collect();
+        let mut out = format!("--- {0}\n+++ {0}\n", self.path.display());
+        if old == new {
+            return out;
+        }
+
+        // Edits are local, so everything outside the common prefix and
+        // suffix is shown as one changed region
+        let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+        let suffix = old[prefix..]
+            .iter()
+            .rev()
+            .zip(new[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let start = prefix.saturating_sub(CONTEXT);
+        let old_end = (old.len() - suffix + CONTEXT).min(old.len());
+        let new_end = (new.len() - suffix + CONTEXT).min(new.len());
+
+        out += &format!(
+            "@@ -{},{} +{},{} @@\n",
+            start + 1,
+            old_end - start,
+            start + 1,
+            new_end - start
+        );
+        for line in &old[start..prefix] {
+            out += &format!(" {line}\n");
+        }
+        for line in &old[prefix..old.len() - suffix] {
+            out += &format!("-{line}\n");
+        }
+        for line in &new[prefix..new.len() - suffix] {
+            out += &format!("+{line}\n");
+        }
+        for line in &old[old.len() - suffix..old_end] {
+            out += &format!(" {line}\n");
+        }
+        out
+    }
+
+    /// Copies the file to a timestamped backup next to it, then replaces it
+    /// with the new contents. Returns the backup's path.
+    ///
+    /// Fails without touching anything when the file changed since it was read.
+    /// A file that did not exist yet is created and `None` is returned.
+    pub fn apply(&self) -> io::Result<Option<PathBuf>> {
+        replace_file(
+            &self.path,
+            self.original.as_bytes(),
+            self.updated.as_bytes(),
+        )
+    }
+}
+
+/// Backs up `path` and replaces its contents `original` with `updated`, as
+/// described for [`FileEdit::apply`]. Works on binary files too.
+pub(crate) fn replace_file(
+    path: &Path,
+    original: &[u8],
+    updated: &[u8],
+) -> io::Result<Option<PathBuf>> {
+    let current = match fs::read(path) {
+        Err(error) if error.kind() == io::ErrorKind::NotFound && original.is_empty() => {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            write_atomic(path, updated)?;
+            return Ok(None);
+        }
+        result => result?,
+    };
+    if current != original {
+        return Err(io::Error::other(format!(
+            "{} changed since it was read",
+            path.display()
+        )));
+    }
+    let backup = backup_path(path);
+    fs::copy(path, &backup)?;
+    write_atomic(path, updated)?;
+    Ok(Some(backup))
+}
+
+/// `<file>.<unix time>.bak` in the same folder. A later time is used when a
+/// backup from the same second exists, so it is never overwritten.
+fn backup_path(path: &Path) -> PathBuf {
+    let mut secs = unix_now();
+    loop {
+        let mut name = path.file_name().unwrap_or_default().to_os_string();
+        name.push(format!(".{secs}.bak"));
+        let backup = path.with_file_name(name);
+        if !backup.exists() {
+            return backup;
+        }
+        secs += 1;
+    }
+}
+
+/// Writes to a temporary file in the same folder and renames it over `path`,
+/// so readers never see a half-written file.
+pub(crate) fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".steam-locater.tmp");
+    let temp = path.with_file_name(name);
+    fs::write(&temp, contents)?;
+    fs::rename(&temp, path)
+}
+
+/// Whether the Steam client is running. Steam rewrites its config files on
+/// exit, so edits made while it runs are lost.
+///
+/// Only detected on Linux, by looking for a `steam` process in `/proc`.
+pub fn steam_running() -> bool {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return false;
+    };
+    entries.filter_map(Result::ok).any(|entry| {
+        let is_pid = entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| name.chars().all(|c| c.is_ascii_digit()));
+        is_pid
+            && fs::read_to_string(entry.path().join("comm"))
+                .is_ok_and(|comm| matches!(comm.trim(), "steam" | "steamwebhelper"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_shows_change_with_context() {
+        let original: String = (1..=10).map(|n| format!("line {n}\n")).collect();
+        let edit = FileEdit {
+            path: PathBuf::from("config.vdf"),
+            updated: original.replace("line 5\n", "line five\nline 5.5\n"),
+            original,
+        };
+        assert_eq!(
+            edit.diff(),
+            "--- config.vdf\n+++ config.vdf\n@@ -2,7 +2,8 @@\n line 2\n line 3\n line 4\n\
+             -line 5\n+line five\n+line 5.5\n line 6\n line 7\n line 8\n"
+        );
+    }
+
+    #[test]
+    fn diff_near_edges_and_unchanged() {
+        let edit = FileEdit {
+            path: PathBuf::from("a"),
+            original: "one\ntwo\n".to_string(),
+            updated: "zero\ntwo\n".to_string(),
+        };
+        assert_eq!(
+            edit.diff(),
+            "--- a\n+++ a\n@@ -1,2 +1,2 @@\n-one\n+zero\n two\n"
+        );
+
+        let edit = FileEdit {
+            updated: edit.original.clone(),
+            ..edit
+        };
+        assert!(edit.is_unchanged());
+        assert_eq!(edit.diff(), "--- a\n+++ a\n");
+    }
+}
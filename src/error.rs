@@ -0,0 +1,41 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Steam could not be located or one of its files could not be read.
+    Steam(steamlocate::Error),
+    /// A file could not be read or written.
+    Io { path: PathBuf, source: io::Error },
+    /// A Steam file has content this crate does not understand.
+    Parse { path: PathBuf },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Steam(error) => write!(f, "{error}"),
+            Self::Io { path, source } => write!(f, "{}: {source}", path.display()),
+            Self::Parse { path } => write!(f, "could not parse {}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Steam(error) => Some(error),
+            Self::Io { source, .. } => Some(source),
+            Self::Parse { .. } => None,
+        }
+    }
+}
+
+impl From<steamlocate::Error> for Error {
+    fn from(error: steamlocate::Error) -> Self {
+        Self::Steam(error)
+    }
+}
@@ -0,0 +1,71 @@
+//! Copying and moving folder trees, keeping symlinks and permissions, and the
+//! timestamps that name backups and trash folders.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Copies the folder `source` to `dest`, which must not exist yet. Symlinks
+/// are copied as links and files keep their permissions.
+pub(crate) fn copy_dir(source: &Path, dest: &Path) -> io::Result<()> {
+    fs::create_dir_all(dest)?;
+    fs::set_permissions(dest, fs::metadata(source)?.permissions())?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let from = entry.path();
+        let to = dest.join(entry.file_name());
+        let kind = entry.file_type()?;
+        if kind.is_symlink() {
+            copy_symlink(&from, &to)?;
+        } else if kind.is_dir() {
+            copy_dir(&from, &to)?;
+        } else {
+            fs::copy(&from, &to)?;
+        }
+    }
+    Ok(())
+}
+
+/// Creates a symlink at `to` pointing where the symlink `from` points.
+#[cfg(unix)]
+fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(from)?, to)
+}
+
+/// Creates a symlink at `to` pointing where the symlink `from` points. Windows
+/// needs to know whether it points to a folder.
+#[cfg(windows)]
+fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    let target = fs::read_link(from)?;
+    if fs::metadata(from).is_ok_and(|metadata| metadata.is_dir()) {
+        std::os::windows::fs::symlink_dir(target, to)
+    } else {
+        std::os::windows::fs::symlink_file(target, to)
+    }
+}
+
+/// Moves the folder `source` to `dest`, creating its parent. A rename is tried
+/// first; across file systems the folder is copied and then deleted.
+pub(crate) fn move_dir(source: &Path, dest: &Path) -> io::Result<()> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    match fs::rename(source, dest) {
+        Err(error) if error.kind() == io::ErrorKind::CrossesDevices => {
+            if let Err(error) = copy_dir(source, dest) {
+                let _ = fs::remove_dir_all(dest);
+                return Err(error);
+            }
+            fs::remove_dir_all(source)
+        }
+        result => result,
+    }
+}
+
+/// The current time in seconds since the Unix epoch, or 0 before it.
+pub(crate) fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
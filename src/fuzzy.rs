@@ -0,0 +1,297 @@
+use crate::Game;
+
+/// A successful fuzzy match of a query against some text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    /// Higher is better. Only comparable between matches of the same query.
+    pub score: i64,
+    /// Char positions in the text that matched the query, in order.
+    pub indices: Vec<usize>,
+}
+
+const MATCH: i64 = 16;
+const WORD_START: i64 = 10;
+const CONSECUTIVE: i64 = 8;
+const FIRST_CHAR: i64 = 4;
+const GAP: i64 = 1;
+
+/// Matches `query` as a case-insensitive subsequence of `text`.
+///
+/// Characters at word starts and runs of consecutive characters score higher, so
+/// acronyms like `ds3` rank "DARK SOULS III" well: roman numeral words also match
+/// their arabic value.
+pub fn fuzzy_match(query: &str, text: &str) -> Option<FuzzyMatch> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if query.is_empty() {
+        return Some(FuzzyMatch {
+            score: 0,
+            indices: Vec::new(),
+        });
+    }
+
+    let chars: Vec<char> = text.chars().collect();
+    let plain: Vec<Unit> = chars
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| Unit {
+            c: lower(c),
+            word_start: is_word_start(&chars, i),
+            source: i..i + 1,
+        })
+        .collect();
+
+    let mut best = best_alignment(&query, &plain);
+    if let Some(numerals) = with_arabic_numerals(&chars) {
+        if let Some(candidate) = best_alignment(&query, &numerals) {
+            if best
+                .as_ref()
+                .is_none_or(|best| candidate.score > best.score)
+            {
+                best = Some(candidate);
+            }
+        }
+    }
+    best
+}
+
+/// Matches `query` against a game's name, app ID and non-Steam label.
+///
+/// The returned indices always refer to the name and are empty when only the
+/// app ID or label matched.
+pub fn match_game(query: &str, game: &Game) -> Option<FuzzyMatch> {
+    let query = query.trim();
+    let by_name = fuzzy_match(query, &game.name);
+
+    let app_id = game.app_id.to_string();
+    let by_app_id = if !query.is_empty() && app_id.starts_with(query) {
+        Some(FuzzyMatch {
+            score: MATCH * 2 * query.len() as i64,
+            indices: Vec::new(),
+        })
+    } else {
+        None
+    };
+
+    let by_label = if game.is_non_steam() {
+        fuzzy_match(query, "Non-Steam").map(|label| FuzzyMatch {
+            score: label.score / 2,
+            indices: Vec::new(),
+        })
+    } else {
+        None
+    };
+
+    [by_name, by_app_id, by_label]
+        .into_iter()
+        .flatten()
+        .max_by_key(|m| m.score)
+}
+
+/// One matchable character, possibly standing in for several chars of the text.
+struct Unit {
+    c: char,
+    word_start: bool,
+    source: std::ops::Range<usize>,
+}
+
+fn lower(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+fn is_word_start(chars: &[char], i: usize) -> bool {
+    let c = chars[i];
+    match i.checked_sub(1).map(|prev| chars[prev]) {
+        None => true,
+        Some(prev) if !prev.is_alphanumeric() => c.is_alphanumeric(),
+        Some(prev) => {
+            (prev.is_lowercase() && c.is_uppercase())
+                || (prev.is_alphabetic() && c.is_ascii_digit())
+                || (prev.is_ascii_digit() && c.is_alphabetic())
+        }
+    }
+}
+
+/// The text with roman numeral words such as `III` or `IV` replaced by digits.
+fn with_arabic_numerals(chars: &[char]) -> Option<Vec<Unit>> {
+    let mut units = Vec::new();
+    let mut replaced = false;
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i].is_alphanumeric() && (i == 0 || !chars[i - 1].is_alphanumeric()) {
+            let end = (i..chars.len())
+                .find(|&j| !chars[j].is_alphanumeric())
+                .unwrap_or(chars.len());
+            let word: String = chars[i..end].iter().collect();
+            if let Some(value) = roman_value(&word) {
+                for (n, digit) in value.to_string().chars().enumerate() {
+                    units.push(Unit {
+                        c: digit,
+                        word_start: n == 0,
+                        source: i..end,
+                    });
+                }
+                replaced = true;
+                i = end;
+                continue;
+            }
+        }
+        units.push(Unit {
+            c: lower(chars[i]),
+            word_start: is_word_start(chars, i),
+            source: i..i + 1,
+        });
+        i += 1;
+    }
+    replaced.then_some(units)
+}
+
+fn roman_value(word: &str) -> Option<u32> {
+    const NUMERALS: [&str; 20] = [
+        "I", "II", "III", "IV", "V", "VI", "VII", "VIII", "IX", "X", "XI", "XII", "XIII", "XIV",
+        "XV", "XVI", "XVII", "XVIII", "XIX", "XX",
+    ];
+    let word = word.to_uppercase();
+    // A lone "I" or "V" is far more often a word or letter than a numeral.
+    if word.len() < 2 && word != "X" {
+        return None;
+    }
+    NUMERALS
+        .iter()
+        .position(|numeral| *numeral == word)
+        .map(|i| i as u32 + 1)
+}
+
+/// Finds the highest scoring way to align `query` with `units`.
+fn best_alignment(query: &[char], units: &[Unit]) -> Option<FuzzyMatch> {
+    let n = units.len();
+    if query.len() > n {
+        return None;
+    }
+
+    // score[i][j]: best score with query[i] matched at units[j]; from[i][j] is the
+    // position of query[i - 1] in that alignment.
+    let mut score = vec![vec![None::<i64>; n]; query.len()];
+    let mut from = vec![vec![0usize; n]; query.len()];
+
+    for (i, &q) in query.iter().enumerate() {
+        for j in 0..n {
+            if units[j].c != q {
+                continue;
+            }
+            let mut bonus = MATCH;
+            if units[j].word_start {
+                bonus += WORD_START;
+            }
+            if i == 0 {
+                let first = if j == 0 { FIRST_CHAR } else { 0 };
+                score[i][j] = Some(bonus + first - j as i64 * GAP / 2);
+                continue;
+            }
+            let mut best: Option<(i64, usize)> = None;
+            for k in 0..j {
+                let Some(prev) = score[i - 1][k] else {
+                    continue;
+                };
+                let step = if k + 1 == j {
+                    CONSECUTIVE
+                } else {
+                    -((j - k - 1) as i64) * GAP
+                };
+                let total = prev + step;
+                if best.is_none_or(|(score, _)| total > score) {
+                    best = Some((total, k));
+                }
+            }
+            if let Some((total, k)) = best {
+                score[i][j] = Some(total + bonus);
+                from[i][j] = k;
+            }
+        }
+    }
+
+    let last = query.len() - 1;
+    let (mut j, total) = (0..n)
+        .filter_map(|j| score[last][j].map(|s| (j, s)))
+        .max_by_key(|&(j, s)| (s, std::cmp::Reverse(j)))?;
+
+    let mut positions = vec![0; query.len()];
+    for i in (0..query.len()).rev() {
+        positions[i] = j;
+        j = from[i][j];
+    }
+
+    let mut indices: Vec<usize> = positions
+        .into_iter()
+        .flat_map(|j| units[j].source.clone())
+        .collect();
+    indices.dedup();
+    Some(FuzzyMatch {
+        score: total,
+        indices,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GameKind;
+
+    fn game(name: &str, app_id: u32, kind: GameKind) -> Game {
+        Game::for_test(name, app_id, kind)
+    }
+
+    #[test]
+    fn acronym_ranks_roman_numeral_title_first() {
+        let games = [
+            game("Dungeons 3", 493_900, GameKind::Steam),
+            game("Dead Island", 91_310, GameKind::Steam),
+            game("DARK SOULS III", 374_320, GameKind::Steam),
+            game("Darksiders 3", 606_280, GameKind::Steam),
+        ];
+        let mut ranked: Vec<(i64, &str)> = games
+            .iter()
+            .filter_map(|game| match_game("ds3", game).map(|m| (m.score, game.name.as_str())))
+            .collect();
+        ranked.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+        assert_eq!(ranked[0].1, "DARK SOULS III");
+        assert!(!ranked.iter().any(|&(_, name)| name == "Dead Island"));
+    }
+
+    #[test]
+    fn roman_numerals_ignore_case() {
+        let m = fuzzy_match("ds3", "Dark Souls iii").unwrap();
+        assert_eq!(m.indices, [0, 5, 11, 12, 13]);
+        assert_eq!(
+            fuzzy_match("ds3", "DARK SOULS III").unwrap().indices,
+            m.indices
+        );
+        assert!(fuzzy_match("1", "Portal I").is_none());
+    }
+
+    #[test]
+    fn matches_app_id_prefix() {
+        let ds3 = game("DARK SOULS III", 374_320, GameKind::Steam);
+        let m = match_game("3743", &ds3).unwrap();
+        assert!(m.indices.is_empty());
+        assert!(match_game("4320", &ds3).is_none());
+    }
+
+    #[test]
+    fn matches_non_steam_label() {
+        let anki = game("Anki", 2_786_274_309, GameKind::Shortcut);
+        let portal = game("Portal", 400, GameKind::Steam);
+        assert!(match_game("nonsteam", &anki).is_some_and(|m| m.indices.is_empty()));
+        assert!(match_game("nonsteam", &portal).is_none());
+        assert_eq!(match_game("ank", &anki).unwrap().indices, [0, 1, 2]);
+    }
+
+    #[test]
+    fn indices_count_chars_not_bytes() {
+        assert_eq!(fuzzy_match("émo", "Pokémon").unwrap().indices, [3, 4, 5]);
+        assert_eq!(fuzzy_match("ÖL", "Ökologie Lab").unwrap().indices, [0, 9]);
+    }
+}
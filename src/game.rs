@@ -0,0 +1,199 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::{fuzzy_match, Account, DiskUsage};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GameKind {
+    /// An app installed from Steam into one of the libraries.
+    Steam,
+    /// A non-Steam game added to Steam as a shortcut.
+    Shortcut,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Game {
+    pub name: String,
+    pub app_id: u32,
+    pub kind: GameKind,
+    /// Root of the Steam installation the game was found through.
+    pub installation: PathBuf,
+    /// Install folder for Steam apps, start directory for shortcuts.
+    pub install_path: PathBuf,
+    /// The `compatdata/<app_id>/pfx` folder, if one exists in any library.
+    pub prefix_path: Option<PathBuf>,
+    /// Name of the compatibility tool mapped to this game, e.g. `proton_9`.
+    pub compat_tool: Option<String>,
+    /// Folder of the mapped compatibility tool; `None` when no tool is mapped
+    /// or the mapped one is not installed.
+    pub compat_tool_path: Option<PathBuf>,
+    /// Library folder the game is installed in. Shortcuts have none.
+    pub library: Option<PathBuf>,
+    /// Size reported by the app manifest, in bytes.
+    pub size_on_disk: Option<u64>,
+    /// The `shadercache/<app_id>` folder, if one exists in any library.
+    pub shader_cache_path: Option<PathBuf>,
+    pub build_id: Option<u64>,
+    /// When the app was last updated, in seconds since the Unix epoch.
+    pub last_updated: Option<u64>,
+    /// When any account last played the game, in seconds since the Unix epoch.
+    pub last_played: Option<u64>,
+    /// Names of the state flags set in the app manifest, e.g. `FullyInstalled`.
+    pub state_flags: Vec<String>,
+    /// Accounts with this shortcut in their `shortcuts.vdf`. Empty for Steam
+    /// apps, which every account of the installation shares.
+    pub owners: Vec<Account>,
+    /// Measured sizes of the game's folders; `None` until [`disk_usage`] has run.
+    ///
+    /// [`disk_usage`]: crate::disk_usage
+    pub disk_usage: Option<DiskUsage>,
+}
+
+/// A folder belonging to a [`Game`] that can be opened or printed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Folder {
+    Install,
+    Prefix,
+    /// `drive_c` inside the prefix.
+    DriveC,
+    /// The `compatdata/<app_id>` folder holding the prefix.
+    CompatData,
+    ShaderCache,
+}
+
+impl Folder {
+    pub fn describe(self) -> &'static str {
+        match self {
+            Self::Install => "game folder",
+            Self::Prefix => "prefix folder",
+            Self::DriveC => "drive_c folder",
+            Self::CompatData => "compatdata folder",
+            Self::ShaderCache => "shader cache folder",
+        }
+    }
+}
+
+impl GameKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Steam => "steam",
+            Self::Shortcut => "shortcut",
+        }
+    }
+}
+
+impl Game {
+    /// A game installed under `/steam` with nothing but a name, app ID and
+    /// kind, for tests to fill in further.
+    #[cfg(test)]
+    pub(crate) fn for_test(name: &str, app_id: u32, kind: GameKind) -> Self {
+        Self {
+            name: name.to_string(),
+            app_id,
+            kind,
+            installation: PathBuf::from("/steam"),
+            install_path: PathBuf::from("/steam/steamapps/common").join(name),
+            prefix_path: None,
+            compat_tool: None,
+            compat_tool_path: None,
+            library: None,
+            size_on_disk: None,
+            shader_cache_path: None,
+            build_id: None,
+            last_updated: None,
+            last_played: None,
+            state_flags: Vec::new(),
+            owners: Vec::new(),
+            disk_usage: None,
+        }
+    }
+
+    pub fn is_non_steam(&self) -> bool {
+        self.kind == GameKind::Shortcut
+    }
+
+    /// Whether the game is mapped to a compatibility tool that is not installed.
+    pub fn compat_tool_missing(&self) -> bool {
+        self.compat_tool.is_some() && self.compat_tool_path.is_none()
+    }
+
+    /// Display names of the accounts owning this shortcut, e.g. `Alice, Bob`.
+    pub fn owner_names(&self) -> String {
+        let names: Vec<String> = self.owners.iter().map(Account::display_name).collect();
+        names.join(", ")
+    }
+
+    /// Whether the game is visible to the account `account_id`: Steam apps
+    /// always are, shortcuts only when the account owns them.
+    pub fn visible_to(&self, account_id: u32) -> bool {
+        !self.is_non_steam()
+            || self
+                .owners
+                .iter()
+                .any(|owner| owner.account_id == account_id)
+    }
+
+    /// Path of `folder` for this game, or `None` when the game has no such folder.
+    pub fn folder(&self, folder: Folder) -> Option<PathBuf> {
+        match folder {
+            Folder::Install => Some(self.install_path.clone()),
+            Folder::Prefix => self.prefix_path.clone(),
+            Folder::DriveC => self.prefix_path.as_ref().map(|pfx| pfx.join("drive_c")),
+            Folder::CompatData => self
+                .prefix_path
+                .as_ref()
+                .and_then(|pfx| pfx.parent())
+                .map(Path::to_path_buf),
+            Folder::ShaderCache => self.shader_cache_path.clone(),
+        }
+    }
+
+    /// Deletes the game's shader cache folder. Steam compiles the shaders again
+    /// the next time the game starts.
+    pub fn clear_shader_cache(&mut self) -> io::Result<()> {
+        if let Some(path) = &self.shader_cache_path {
+            match fs::remove_dir_all(path) {
+                Err(error) if error.kind() != io::ErrorKind::NotFound => return Err(error),
+                _ => {}
+            }
+        }
+        self.shader_cache_path = None;
+        if let Some(usage) = &mut self.disk_usage {
+            usage.shader_cache = 0;
+        }
+        Ok(())
+    }
+}
+
+/// Finds the games matching `query`, which is either an app ID or a fuzzy name.
+///
+/// An exact app ID or a case-insensitive exact name wins over fuzzy matches,
+/// which are returned best first.
+pub fn find_games<'a>(games: &'a [Game], query: &str) -> Vec<&'a Game> {
+    if let Ok(app_id) = query.parse::<u32>() {
+        let by_id: Vec<&Game> = games.iter().filter(|game| game.app_id == app_id).collect();
+        if !by_id.is_empty() {
+            return by_id;
+        }
+    }
+
+    let query = query.to_lowercase();
+    let exact: Vec<&Game> = games
+        .iter()
+        .filter(|game| game.name.to_lowercase() == query)
+        .collect();
+    if !exact.is_empty() {
+        return exact;
+    }
+
+    let mut fuzzy: Vec<(i64, &Game)> = games
+        .iter()
+        .filter_map(|game| fuzzy_match(&query, &game.name).map(|m| (m.score, game)))
+        .collect();
+    fuzzy.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+    fuzzy.into_iter().map(|(_, game)| game).collect()
+}
@@ -0,0 +1,104 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use steamlocate::SteamDir;
+
+/// How a Steam installation was installed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InstallKind {
+    Native,
+    Flatpak,
+    Snap,
+    /// Found by [`SteamDir::locate`] outside the known Linux locations, or given by hand.
+    Other,
+}
+
+impl fmt::Display for InstallKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Native => "native",
+            Self::Flatpak => "Flatpak",
+            Self::Snap => "Snap",
+            Self::Other => "other",
+        })
+    }
+}
+
+/// A Steam installation root found on this machine.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Installation {
+    pub path: PathBuf,
+    pub kind: InstallKind,
+}
+
+/// Finds every Steam installation in the known native, Flatpak and Snap locations.
+///
+/// The same installation reached through several symlinks is only listed once. The
+/// one [`SteamDir::locate`] would pick comes first.
+pub fn find_installations() -> Vec<Installation> {
+    let mut candidates = Vec::new();
+    if let Ok(located) = SteamDir::locate() {
+        candidates.push((located.path().to_path_buf(), InstallKind::Other));
+    }
+
+    if let Some(home) = std::env::var_os("HOME").map(PathBuf::from) {
+        let snap = home.join("snap");
+        let flatpak = home.join(".var/app/com.valvesoftware.Steam");
+        candidates.extend(
+            [
+                home.join(".local/share/Steam"),
+                home.join(".steam/steam"),
+                home.join(".steam/root"),
+            ]
+            .map(|path| (path, InstallKind::Native)),
+        );
+        candidates.extend(
+            [
+                flatpak.join(".local/share/Steam"),
+                flatpak.join(".steam/steam"),
+                flatpak.join(".steam/root"),
+            ]
+            .map(|path| (path, InstallKind::Flatpak)),
+        );
+        candidates.extend(
+            [
+                snap.join("steam/common/.local/share/Steam"),
+                snap.join("steam/common/.steam/steam"),
+                snap.join("steam/common/.steam/root"),
+            ]
+            .map(|path| (path, InstallKind::Snap)),
+        );
+        candidates.push((
+            home.join("Library/Application Support/Steam"),
+            InstallKind::Native,
+        ));
+    }
+
+    let mut installations: Vec<(PathBuf, Installation)> = Vec::new();
+    for (path, kind) in candidates {
+        if !is_steam_root(&path) {
+            continue;
+        }
+        let real_path = path.canonicalize().unwrap_or_else(|_| path.clone());
+        match installations
+            .iter_mut()
+            .find(|(real, _)| *real == real_path)
+        {
+            // The located path has no kind yet; take it from the known location
+            Some((_, found)) if found.kind == InstallKind::Other => found.kind = kind,
+            Some(_) => {}
+            None => installations.push((real_path, Installation { path, kind })),
+        }
+    }
+    installations
+        .into_iter()
+        .map(|(_, installation)| installation)
+        .collect()
+}
+
+/// Whether `path` looks like a Steam installation root.
+pub fn is_steam_root(path: &Path) -> bool {
+    path.join("steamapps").is_dir()
+}
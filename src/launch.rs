@@ -0,0 +1,422 @@
+//! Per-account launch options: `LaunchOptions` in `localconfig.vdf` for Steam
+//! apps and in `shortcuts.vdf` for non-Steam shortcuts.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::edit::replace_file;
+use crate::userdata::read_shortcuts;
+use crate::vdf::{self, BinaryValue};
+use crate::{Account, Error, Game};
+
+/// Stands for the game's own command line in launch options.
+const COMMAND: &str = "%command%";
+
+/// The object holding per-app settings in `localconfig.vdf`.
+const LOCALCONFIG_APPS: [&str; 5] = ["UserLocalConfigStore", "Software", "Valve", "Steam", "apps"];
+
+/// The launch options one account set for a game.
+#[derive(Clone, Debug, Serialize)]
+pub struct LaunchOptions {
+    pub account: Account,
+    pub options: String,
+}
+
+/// A common addition to launch options that can be switched on and off.
+#[derive(Clone, Copy, Debug)]
+pub struct LaunchPreset {
+    /// The variable or wrapper command it sets, e.g. `PROTON_LOG`.
+    pub name: &'static str,
+    /// What is added to the options, e.g. `PROTON_LOG=1`.
+    pub option: &'static str,
+    pub description: &'static str,
+}
+
+pub const LAUNCH_PRESETS: [LaunchPreset; 3] = [
+    LaunchPreset {
+        name: "PROTON_LOG",
+        option: "PROTON_LOG=1",
+        description: "write a Proton log to ~/steam-<app id>.log",
+    },
+    LaunchPreset {
+        name: "DXVK_HUD",
+        option: "DXVK_HUD=fps",
+        description: "show the frame rate in DXVK games",
+    },
+    LaunchPreset {
+        name: "gamemoderun",
+        option: "gamemoderun",
+        description: "run the game with Feral GameMode",
+    },
+];
+
+impl LaunchPreset {
+    /// Whether it sets an environment variable rather than wrapping the command.
+    fn is_variable(&self) -> bool {
+        self.option.contains('=')
+    }
+
+    /// Whether `word`, from before `%command%`, is this preset with any value.
+    fn matches(&self, word: &str) -> bool {
+        if self.is_variable() {
+            word.split_once('=')
+                .is_some_and(|(name, _)| name == self.name)
+        } else {
+            word == self.option
+        }
+    }
+}
+
+/// Whether `options` already use `preset`, with any value.
+pub fn has_preset(options: &str, preset: &LaunchPreset) -> bool {
+    split_command(options)
+        .0
+        .iter()
+        .any(|word| preset.matches(word))
+}
+
+/// Removes `preset` from `options` when they use it, or adds it otherwise.
+///
+/// Variables are put first and wrapper commands right before `%command%`,
+/// which is added when the options were only arguments to the game.
+pub fn toggle_preset(options: &str, preset: &LaunchPreset) -> String {
+    let (mut before, after) = split_command(options);
+    if before.iter().any(|word| preset.matches(word)) {
+        before.retain(|word| !preset.matches(word));
+    } else if preset.is_variable() {
+        before.insert(0, preset.option);
+    } else {
+        before.push(preset.option);
+    }
+    if before.is_empty() {
+        return after.join(" ");
+    }
+    before.push(COMMAND);
+    before.extend(after);
+    before.join(" ")
+}
+
+/// The words before `%command%` and the game's arguments after it. Options
+/// without `%command%` are all arguments.
+fn split_command(options: &str) -> (Vec<&str>, Vec<&str>) {
+    let words: Vec<&str> = options.split_whitespace().collect();
+    match words.iter().position(|&word| word == COMMAND) {
+        Some(i) => (words[..i].to_vec(), words[i + 1..].to_vec()),
+        None => (Vec::new(), words),
+    }
+}
+
+/// The non-empty launch options each of `accounts` set for `game`.
+pub fn find_launch_options(game: &Game, accounts: &[Account]) -> Vec<LaunchOptions> {
+    accounts
+        .iter()
+        .filter_map(|account| {
+            let options = read_launch_options(game, account)?;
+            (!options.is_empty()).then(|| LaunchOptions {
+                account: account.clone(),
+                options,
+            })
+        })
+        .collect()
+}
+
+fn read_launch_options(game: &Game, account: &Account) -> Option<String> {
+    let user_dir = user_dir(game, account);
+    if game.is_non_steam() {
+        let shortcuts = read_shortcuts(&user_dir)?;
+        let options = shortcuts
+            .entries()
+            .iter()
+            .find(|(_, shortcut)| {
+                shortcut.get("appid").and_then(BinaryValue::as_int) == Some(game.app_id)
+            })?
+            .1
+            .get("LaunchOptions")?
+            .as_str()?;
+        Some(options.to_string())
+    } else {
+        let text = fs::read_to_string(user_dir.join("config").join("localconfig.vdf")).ok()?;
+        let app_id = game.app_id.to_string();
+        let path: Vec<&str> = LOCALCONFIG_APPS
+            .into_iter()
+            .chain([app_id.as_str()])
+            .collect();
+        vdf::get_text_value(&text, &path, "LaunchOptions")
+    }
+}
+
+/// The accounts that can have launch options for `game`: the owners of a
+/// shortcut, or for a Steam app every account with a `userdata` folder.
+pub fn launch_option_accounts(game: &Game, accounts: &[Account]) -> Vec<Account> {
+    accounts
+        .iter()
+        .filter(|account| {
+            if game.is_non_steam() {
+                game.owners.contains(account)
+            } else {
+                user_dir(game, account).is_dir()
+            }
+        })
+        .cloned()
+        .collect()
+}
+
+/// `userdata/<account id>` in the game's installation.
+fn user_dir(game: &Game, account: &Account) -> PathBuf {
+    game.installation
+        .join("userdata")
+        .join(account.account_id.to_string())
+}
+
+/// A pending change to the launch options one account set for a game.
+#[derive(Clone, Debug)]
+pub struct LaunchOptionsEdit {
+    /// The `localconfig.vdf` or `shortcuts.vdf` being changed.
+    pub path: PathBuf,
+    pub account: Account,
+    pub old: String,
+    pub new: String,
+    original: Vec<u8>,
+    updated: Vec<u8>,
+}
+
+impl LaunchOptionsEdit {
+    pub fn is_unchanged(&self) -> bool {
+        self.original == self.updated
+    }
+
+    /// Backs up the file and replaces it, as [`FileEdit::apply`] does.
+    ///
+    /// [`FileEdit::apply`]: crate::FileEdit::apply
+    pub fn apply(&self) -> io::Result<Option<PathBuf>> {
+        replace_file(&self.path, &self.original, &self.updated)
+    }
+}
+
+/// Prepares setting the launch options `account` uses for `game` to `options`.
+/// Nothing is written until the edit is applied.
+pub fn set_launch_options(
+    game: &Game,
+    account: &Account,
+    options: &str,
+) -> crate::Result<LaunchOptionsEdit> {
+    let config_dir = user_dir(game, account).join("config");
+    if game.is_non_steam() {
+        set_shortcut_options(&config_dir.join("shortcuts.vdf"), game, account, options)
+    } else {
+        set_localconfig_options(&config_dir.join("localconfig.vdf"), game, account, options)
+    }
+}
+
+fn set_localconfig_options(
+    path: &Path,
+    game: &Game,
+    account: &Account,
+    options: &str,
+) -> crate::Result<LaunchOptionsEdit> {
+    let original = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(source) => {
+            return Err(Error::Io {
+                path: path.to_path_buf(),
+                source,
+            })
+        }
+    };
+    let app_id = game.app_id.to_string();
+    let apps: Vec<&str> = LOCALCONFIG_APPS
+        .into_iter()
+        .chain([app_id.as_str()])
+        .collect();
+    let Some(updated) = vdf::set_text_value(&original, &apps, "LaunchOptions", options, true)
+    else {
+        return Err(Error::Parse {
+            path: path.to_path_buf(),
+        });
+    };
+    Ok(LaunchOptionsEdit {
+        path: path.to_path_buf(),
+        account: account.clone(),
+        old: vdf::get_text_value(&original, &apps, "LaunchOptions").unwrap_or_default(),
+        new: options.to_string(),
+        original: original.into_bytes(),
+        updated: updated.into_bytes(),
+    })
+}
+
+fn set_shortcut_options(
+    path: &Path,
+    game: &Game,
+    account: &Account,
+    options: &str,
+) -> crate::Result<LaunchOptionsEdit> {
+    let original = fs::read(path).map_err(|source| Error::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let parse_error = || Error::Parse {
+        path: path.to_path_buf(),
+    };
+    let mut root = vdf::parse_binary(&original).ok_or_else(parse_error)?;
+    // Refuse files that would not be written back exactly as they were read
+    if vdf::write_binary(&root) != original {
+        return Err(parse_error());
+    }
+    let shortcut = root
+        .entries_mut()
+        .iter_mut()
+        .find(|(key, _)| key.eq_ignore_ascii_case("shortcuts"))
+        .map(|(_, shortcuts)| shortcuts.entries_mut())
+        .and_then(|shortcuts| {
+            shortcuts.iter_mut().find(|(_, shortcut)| {
+                shortcut.get("appid").and_then(BinaryValue::as_int) == Some(game.app_id)
+            })
+        })
+        .map(|(_, shortcut)| shortcut)
+        .ok_or_else(|| Error::Io {
+            path: path.to_path_buf(),
+            source: io::Error::new(
+                io::ErrorKind::NotFound,
+                format!(
+                    "{} has no shortcut for {}",
+                    account.display_name(),
+                    game.name
+                ),
+            ),
+        })?;
+    let old = shortcut
+        .get("LaunchOptions")
+        .and_then(BinaryValue::as_str)
+        .unwrap_or_default()
+        .to_string();
+    shortcut.set("LaunchOptions", BinaryValue::Str(options.to_string()));
+    Ok(LaunchOptionsEdit {
+        path: path.to_path_buf(),
+        account: account.clone(),
+        old,
+        new: options.to_string(),
+        updated: vdf::write_binary(&root),
+        original,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROTON_LOG: &LaunchPreset = &LAUNCH_PRESETS[0];
+    const DXVK_HUD: &LaunchPreset = &LAUNCH_PRESETS[1];
+    const GAMEMODE: &LaunchPreset = &LAUNCH_PRESETS[2];
+
+    #[test]
+    fn toggles_presets_without_command() {
+        assert_eq!(toggle_preset("", PROTON_LOG), "PROTON_LOG=1 %command%");
+        assert_eq!(
+            toggle_preset("-novid", GAMEMODE),
+            "gamemoderun %command% -novid"
+        );
+        assert_eq!(
+            toggle_preset("-novid", DXVK_HUD),
+            "DXVK_HUD=fps %command% -novid"
+        );
+        assert!(!has_preset("-novid", GAMEMODE));
+    }
+
+    #[test]
+    fn toggles_presets_around_command() {
+        let options = "gamemoderun %command% -novid";
+        let options = toggle_preset(options, PROTON_LOG);
+        assert_eq!(options, "PROTON_LOG=1 gamemoderun %command% -novid");
+        assert!(has_preset(&options, PROTON_LOG));
+        let options = toggle_preset(&options, GAMEMODE);
+        assert_eq!(options, "PROTON_LOG=1 %command% -novid");
+        let options = toggle_preset(&options, PROTON_LOG);
+        assert_eq!(options, "-novid");
+    }
+
+    #[test]
+    fn removes_preset_with_other_value() {
+        let options = "DXVK_HUD=full mangohud %command%";
+        assert!(has_preset(options, DXVK_HUD));
+        assert_eq!(toggle_preset(options, DXVK_HUD), "mangohud %command%");
+        assert!(!has_preset("DXVK_HUDX=1 %command%", DXVK_HUD));
+    }
+
+    fn shortcut_game(installation: &Path, account: &Account) -> Game {
+        Game {
+            installation: installation.to_path_buf(),
+            install_path: PathBuf::from("/opt/anki"),
+            owners: vec![account.clone()],
+            ..Game::for_test("Anki", 2_786_274_309, crate::GameKind::Shortcut)
+        }
+    }
+
+    #[test]
+    fn sets_shortcut_options_in_place() {
+        let installation =
+            std::env::temp_dir().join(format!("steam-locater-launch-{}", std::process::id()));
+        let account = Account {
+            account_id: 12345,
+            steam_id: 76_561_197_960_278_073,
+            account_name: Some("player".to_string()),
+            persona_name: None,
+            most_recent: true,
+        };
+        let game = shortcut_game(&installation, &account);
+        let config = installation.join("userdata").join("12345").join("config");
+        fs::create_dir_all(&config).unwrap();
+        let shortcut = |options: Option<&str>| {
+            let mut entries = vec![
+                ("appid".to_string(), BinaryValue::Int(game.app_id)),
+                ("AppName".to_string(), BinaryValue::Str("Anki".to_string())),
+            ];
+            entries.extend(
+                options.map(|o| ("LaunchOptions".to_string(), BinaryValue::Str(o.to_string()))),
+            );
+            entries.push(("LastPlayTime".to_string(), BinaryValue::Long(7)));
+            BinaryValue::Map(vec![(
+                "shortcuts".to_string(),
+                BinaryValue::Map(vec![("0".to_string(), BinaryValue::Map(entries))]),
+            )])
+        };
+        let original = vdf::write_binary(&shortcut(Some("-v")));
+        fs::write(config.join("shortcuts.vdf"), &original).unwrap();
+
+        let edit = set_launch_options(&game, &account, "gamemoderun %command% -v").unwrap();
+        assert_eq!(
+            (edit.old.as_str(), edit.new.as_str()),
+            ("-v", "gamemoderun %command% -v")
+        );
+        assert_eq!(
+            edit.updated,
+            vdf::write_binary(&shortcut(Some("gamemoderun %command% -v")))
+        );
+        assert!(set_launch_options(&game, &account, "-v")
+            .unwrap()
+            .is_unchanged());
+
+        let other = Game {
+            app_id: 1,
+            ..game.clone()
+        };
+        assert!(set_launch_options(&other, &account, "").is_err());
+
+        fs::write(
+            config.join("shortcuts.vdf"),
+            vdf::write_binary(&shortcut(None)),
+        )
+        .unwrap();
+        let edit = set_launch_options(&game, &account, "-v").unwrap();
+        let mut expected = shortcut(None);
+        expected.entries_mut()[0].1.entries_mut()[0]
+            .1
+            .set("LaunchOptions", BinaryValue::Str("-v".to_string()));
+        assert_eq!(edit.updated, vdf::write_binary(&expected));
+
+        fs::remove_dir_all(&installation).unwrap();
+    }
+}
@@ -0,0 +1,52 @@
+//! Discovery of Steam games, non-Steam shortcuts and their Proton/Wine prefixes.
+//!
+//! ```no_run
+//! for game in steam_locater::scan()? {
+//!     println!("{} ({})", game.name, game.install_path.display());
+//! }
+//! # Ok::<_, steam_locater::Error>(())
+//! ```
+
+mod accounts;
+mod archive;
+mod backup;
+mod edit;
+mod error;
+mod files;
+mod fuzzy;
+mod game;
+mod install;
+mod launch;
+mod orphans;
+mod reset;
+mod save_backup;
+mod saves;
+mod scanner;
+mod sort;
+mod tools;
+mod usage;
+mod userdata;
+mod vdf;
+
+pub use accounts::{find_accounts, find_userdata, Account, UserdataFolder, UserdataKind};
+pub use archive::create_archive;
+pub use backup::{back_up_prefix, find_backups, restore_backup, Backup};
+pub use edit::{steam_running, FileEdit};
+pub use error::{Error, Result};
+pub use fuzzy::{fuzzy_match, match_game, FuzzyMatch};
+pub use game::{find_games, Folder, Game, GameKind};
+pub use install::{find_installations, is_steam_root, InstallKind, Installation};
+pub use launch::{
+    find_launch_options, has_preset, launch_option_accounts, set_launch_options, toggle_preset,
+    LaunchOptions, LaunchOptionsEdit, LaunchPreset, LAUNCH_PRESETS,
+};
+pub use orphans::{archive_orphan, delete_orphan, find_orphans, Orphan, OrphanKind};
+pub use reset::{reset_prefix, PrefixReset};
+pub use save_backup::{
+    back_up_saves, find_save_backups, restore_saves, save_folders, SaveBackup, SavedFolder,
+};
+pub use saves::{find_save_locations, SaveLocation};
+pub use scanner::{scan, scan_all, Scanner};
+pub use sort::SortKey;
+pub use tools::{find_tools, map_compat_tool, resolve_tool, Tool, ToolKind};
+pub use usage::{dir_size, disk_usage, DiskUsage};
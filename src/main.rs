@@ -1,282 +1,697 @@
-use std::io::stdout;
-use std::path::PathBuf;
-use std::process::Command;
+mod cli;
+mod clipboard;
+mod config;
+mod opener;
+mod output;
+mod tui;
 
-use crossterm::{
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-};
-use ratatui::{
-    backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout},
-    style::{Color, Style},
-    text::Span,
-    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
-    Terminal,
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use cli::{Command, OrphanAction};
+use config::Config;
+use steam_locater::{
+    archive_orphan, back_up_prefix, back_up_saves, delete_orphan, dir_size, find_accounts,
+    find_backups, find_installations, find_launch_options, find_orphans, find_save_backups,
+    find_save_locations, find_userdata, fuzzy_match, is_steam_root, launch_option_accounts,
+    map_compat_tool, reset_prefix, restore_backup, restore_saves, scan_all, set_launch_options,
+    steam_running, toggle_preset, Folder, Game, InstallKind, Installation, Orphan, Scanner, Tool,
+    LAUNCH_PRESETS,
 };
-use steamlocate::SteamDir;
 
-#[derive(Clone)]
-struct Game {
-    name: String,
-    app_id: u32,
-    is_non_steam: bool,
-    path: PathBuf,
+fn main() {
+    let cli = match cli::parse(std::env::args().skip(1)) {
+        Ok(cli) => cli,
+        Err(message) => {
+            eprintln!("error: {message}\n\n{}", cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    if let Err(error) = run(cli) {
+        fail(&error.to_string());
+    }
 }
 
-struct App {
-    items: Vec<Game>,
-    filtered_items: Vec<Game>,
-    search_query: String,
-    in_search_mode: bool,
-    status_message: String,
-    state: ListState,
-}
+fn run(cli: cli::Cli) -> Result<(), Box<dyn std::error::Error>> {
+    let steam_dir = cli.steam_dir.as_deref();
+    match cli.command {
+        Command::Help => println!("{}", cli::USAGE),
+        Command::ConfigPath => match config::config_path() {
+            Some(path) => println!("{}", path.display()),
+            None => fail("neither XDG_CONFIG_HOME nor HOME is set"),
+        },
+        Command::ConfigInit { force } => {
+            let Some(path) = config::config_path() else {
+                fail("neither XDG_CONFIG_HOME nor HOME is set");
+            };
+            match config::init(&path, force) {
+                Ok(()) => println!("Wrote {}", path.display()),
+                Err(message) => fail(&message),
+            }
+        }
+        Command::Installs => {
+            for installation in find_installations() {
+                println!("{}\t{}", installation.kind, installation.path.display());
+            }
+        }
+        Command::List { format, account } => {
+            let config = load_config();
+            let installations = choose_installations(steam_dir, &config, false);
+            let mut items = visible_games(scan(&installations, &config)?, &config);
+            if let Some(query) = account {
+                let account_ids: Vec<u32> = installations
+                    .iter()
+                    .flat_map(|installation| find_accounts(&installation.path))
+                    .filter(|account| account.matches(&query))
+                    .map(|account| account.account_id)
+                    .collect();
+                if account_ids.is_empty() {
+                    fail(&format!("no account matches '{query}'"));
+                }
+                items.retain(|game| account_ids.iter().any(|&id| game.visible_to(id)));
+            }
+            output::print_games(&items, format)?;
+        }
+        Command::Path { folder, query } => {
+            let config = load_config();
+            let game = &resolve(steam_dir, &config, &query)?;
+            match output::existing_folder(game, folder) {
+                Ok(path) => println!("{}", path.display()),
+                Err(message) => fail(&message),
+            }
+        }
+        Command::Orphans {
+            format,
+            action,
+            app_ids,
+            yes,
+        } => {
+            let config = load_config();
+            let installations = choose_installations(steam_dir, &config, false);
+            let orphans = find_orphans(&scanners(&installations, &config)?)?;
+            let mut orphans: Vec<(Orphan, u64)> = orphans
+                .into_iter()
+                .filter(|orphan| app_ids.is_empty() || app_ids.contains(&orphan.app_id))
+                .map(|orphan| {
+                    let size = dir_size(&orphan.path);
+                    (orphan, size)
+                })
+                .collect();
+            let Some(action) = action else {
+                output::print_orphans(&orphans, format)?;
+                return Ok(());
+            };
+            if let Some(missing) = app_ids
+                .iter()
+                .find(|&&app_id| !orphans.iter().any(|(orphan, _)| orphan.app_id == app_id))
+            {
+                fail(&format!("{missing} has no orphaned folder"));
+            }
+            if orphans.is_empty() {
+                println!("No orphaned folders.");
+                return Ok(());
+            }
 
-impl App {
-    fn new(items: Vec<Game>) -> Self {
-        let filtered_items = items.clone();
-        Self {
-            items,
-            filtered_items,
-            search_query: String::new(),
-            in_search_mode: false,
-            status_message: "Use '/' to search, 'q' to exit.".to_string(),
-            state: ListState::default(),
+            let archive_dir = match action {
+                OrphanAction::Delete => None,
+                OrphanAction::Archive => Some(config.archive_dir().unwrap_or_else(|| {
+                    fail("set archive_dir in the config, or XDG_DATA_HOME or HOME")
+                })),
+            };
+            let total: u64 = orphans.iter().map(|(_, size)| size).sum();
+            let question = match &archive_dir {
+                None => format!(
+                    "Delete {} orphaned folders ({})?",
+                    orphans.len(),
+                    output::format_size(total)
+                ),
+                Some(dir) => format!(
+                    "Archive {} orphaned folders ({}) to {} and delete them?",
+                    orphans.len(),
+                    output::format_size(total),
+                    dir.display()
+                ),
+            };
+            if !yes && !confirm(&question)? {
+                return Ok(());
+            }
+            for (orphan, size) in orphans.drain(..) {
+                let result = match &archive_dir {
+                    None => delete_orphan(&orphan).map(|()| {
+                        format!(
+                            "Deleted {} ({})",
+                            orphan.path.display(),
+                            output::format_size(size)
+                        )
+                    }),
+                    Some(dir) => archive_orphan(&orphan, dir).map(|archive| {
+                        format!(
+                            "Archived {} to {}",
+                            orphan.path.display(),
+                            archive.display()
+                        )
+                    }),
+                };
+                match result {
+                    Ok(message) => println!("{message}"),
+                    Err(error) => fail(&format!("{}: {error}", orphan.path.display())),
+                }
+            }
         }
-    }
+        Command::SetTool {
+            query,
+            tool,
+            dry_run,
+            force,
+        } => {
+            let config = load_config();
+            let installations = choose_installations(steam_dir, &config, false);
+            let scanners = scanners(&installations, &config)?;
+            let items = scan_all(&scanners)?;
+            let game =
+                output::resolve_game(&items, &query).unwrap_or_else(|message| fail(&message));
+            let tools = scanners
+                .iter()
+                .find(|scanner| scanner.steam_dir().path() == game.installation)
+                .map(Scanner::tools)
+                .unwrap_or_default();
+            let Some(tool) = tools
+                .iter()
+                .find(|known| known.name == tool || known.display_name == tool)
+            else {
+                let names: Vec<&str> = tools.iter().map(|tool| tool.name.as_str()).collect();
+                fail(&format!(
+                    "no installed tool named '{tool}'; installed tools: {}",
+                    names.join(", ")
+                ));
+            };
 
-    fn update_filter(&mut self) {
-        self.filtered_items = self
-            .items
-            .iter()
-            .filter(|game| {
-                game.name
-                    .to_lowercase()
-                    .contains(&self.search_query.to_lowercase())
-            })
-            .cloned()
-            .collect();
-        // Reset selection if out of bounds
-        if let Some(selected) = self.state.selected() {
-            if selected >= self.filtered_items.len() {
-                self.state.select(if self.filtered_items.is_empty() {
-                    None
-                } else {
-                    Some(0)
+            let edit = map_compat_tool(&game.installation, game.app_id, &tool.name)?;
+            if edit.is_unchanged() {
+                println!("{} already uses {}.", game.name, tool.name);
+            } else if dry_run {
+                print!("{}", edit.diff());
+            } else {
+                require_steam_stopped(force, "would overwrite the change when it exits");
+                match edit.apply() {
+                    Ok(Some(backup)) => println!(
+                        "Mapped {} to {}. The original is saved as {}.",
+                        game.name,
+                        tool.name,
+                        backup.display()
+                    ),
+                    Ok(None) => println!("Mapped {} to {}.", game.name, tool.name),
+                    Err(error) => fail(&error.to_string()),
+                }
+            }
+        }
+        Command::ClearShaderCache { query, yes, force } => {
+            let config = load_config();
+            let mut game = resolve(steam_dir, &config, &query)?;
+            let Some(path) = game.shader_cache_path.clone() else {
+                println!("{} has no shader cache.", game.name);
+                return Ok(());
+            };
+            require_steam_stopped(force, "may be using the shader cache");
+            let size = output::format_size(dir_size(&path));
+            let question = format!("Delete {} ({size})?", path.display());
+            if !yes && !confirm(&question)? {
+                return Ok(());
+            }
+            match game.clear_shader_cache() {
+                Ok(()) => println!("Cleared the shader cache of {} ({size}).", game.name),
+                Err(error) => fail(&format!("{}: {error}", path.display())),
+            }
+        }
+        Command::Backup { query } => {
+            let config = load_config();
+            let game = &resolve(steam_dir, &config, &query)?;
+            let dir = config
+                .backup_dir()
+                .unwrap_or_else(|| fail("set backup_dir in the config, or XDG_DATA_HOME or HOME"));
+            match back_up_prefix(game, &dir) {
+                Ok(backup) => println!(
+                    "Backed up the prefix of {} to {}.",
+                    game.name,
+                    backup.archive.display()
+                ),
+                Err(error) => fail(&error.to_string()),
+            }
+        }
+        Command::Backups { query, format } => {
+            let config = load_config();
+            let dir = config
+                .backup_dir()
+                .unwrap_or_else(|| fail("set backup_dir in the config, or XDG_DATA_HOME or HOME"));
+            let mut backups = find_backups(&dir);
+            if let Some(query) = query {
+                backups.retain(|backup| match query.parse::<u32>() {
+                    Ok(app_id) => backup.app_id == app_id,
+                    Err(_) => fuzzy_match(&query, &backup.name).is_some(),
                 });
             }
+            output::print_backups(&backups, format)?;
         }
-    }
-
-    fn enter_search_mode(&mut self) {
-        self.in_search_mode = true;
-    }
-
-    fn exit_search_mode(&mut self) {
-        self.in_search_mode = false;
-        self.search_query.clear();
-        self.update_filter();
-    }
-
-    fn next(&mut self) {
-        let len = self.filtered_items.len();
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i >= len - 1 {
-                    0
+        Command::Restore {
+            query,
+            created,
+            yes,
+            force,
+        } => {
+            let config = load_config();
+            let game = &resolve(steam_dir, &config, &query)?;
+            let dir = config
+                .backup_dir()
+                .unwrap_or_else(|| fail("set backup_dir in the config, or XDG_DATA_HOME or HOME"));
+            let Some(backup) = find_backups(&dir).into_iter().find(|backup| {
+                backup.app_id == game.app_id && created.is_none_or(|time| backup.created == time)
+            }) else {
+                fail(&match created {
+                    Some(time) => format!("{} has no backup made at {time}", game.name),
+                    None => format!("{} has no backups", game.name),
+                });
+            };
+            require_steam_stopped(force, "the game may be using its prefix");
+            let question = format!(
+                "Replace the prefix of {} with the backup from {}?",
+                game.name,
+                output::format_timestamp(backup.created)
+            );
+            if !yes && !confirm(&question)? {
+                return Ok(());
+            }
+            match restore_backup(&backup, game) {
+                Ok(pfx) => println!(
+                    "Restored {} from {}.",
+                    pfx.display(),
+                    backup.archive.display()
+                ),
+                Err(error) => fail(&error.to_string()),
+            }
+        }
+        Command::ResetPrefix {
+            query,
+            keep_saves,
+            yes,
+            force,
+        } => {
+            let config = load_config();
+            let game = &resolve(steam_dir, &config, &query)?;
+            let Some(compat_data) = game.folder(Folder::CompatData) else {
+                fail(&format!("{} has no prefix", game.name));
+            };
+            let trash_dir = config
+                .trash_dir()
+                .unwrap_or_else(|| fail("set trash_dir in the config, or XDG_DATA_HOME or HOME"));
+            require_steam_stopped(force, "the game may be using its prefix");
+            let question = format!(
+                "Move {} ({}) to {}{}?",
+                compat_data.display(),
+                output::format_size(dir_size(&compat_data)),
+                trash_dir.display(),
+                if keep_saves {
+                    ", keeping drive_c/users/steamuser"
                 } else {
-                    i + 1
+                    ""
                 }
+            );
+            if !yes && !confirm(&question)? {
+                return Ok(());
             }
-            None => 0,
-        };
-        self.state.select(Some(i));
-    }
-
-    fn previous(&mut self) {
-        let len = self.filtered_items.len();
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    len - 1
-                } else {
-                    i - 1
+            match reset_prefix(game, &trash_dir, keep_saves) {
+                Ok(reset) => {
+                    println!(
+                        "Moved {} ({}) to {}:",
+                        reset.compat_data.display(),
+                        output::format_size(reset.size),
+                        reset.trash.display()
+                    );
+                    for path in &reset.moved {
+                        println!("  {}", path.display());
+                    }
+                    match &reset.kept {
+                        Some(saves) => println!("Kept {} in the new prefix.", saves.display()),
+                        None => println!("Proton creates a new prefix when the game starts."),
+                    }
                 }
+                Err(error) => fail(&error.to_string()),
             }
-            None => 0,
-        };
-        self.state.select(Some(i));
-    }
+        }
+        Command::Saves { query, format } => {
+            let config = load_config();
+            let game = &resolve(steam_dir, &config, &query)?;
+            if game.prefix_path.is_none() {
+                fail(&format!("{} has no prefix", game.name));
+            }
+            output::print_saves(&find_save_locations(game), format)?;
+        }
+        Command::Accounts { format } => {
+            let config = load_config();
+            let mut accounts = Vec::new();
+            for installation in choose_installations(steam_dir, &config, false) {
+                for account in find_accounts(&installation.path) {
+                    if !accounts.contains(&account) {
+                        accounts.push(account);
+                    }
+                }
+            }
+            output::print_accounts(&accounts, format)?;
+        }
+        Command::LaunchOptions { query, format } => {
+            let config = load_config();
+            let game = &resolve(steam_dir, &config, &query)?;
+            let options = find_launch_options(game, &find_accounts(&game.installation));
+            if options.is_empty() {
+                println!("No account set launch options for {}.", game.name);
+                return Ok(());
+            }
+            output::print_launch_options(&options, format)?;
+        }
+        Command::SetLaunchOptions {
+            query,
+            options,
+            presets,
+            account,
+            dry_run,
+            force,
+        } => {
+            let config = load_config();
+            let game = &resolve(steam_dir, &config, &query)?;
+            let candidates = launch_option_accounts(game, &find_accounts(&game.installation));
+            let account = match (&account, &candidates[..]) {
+                (Some(query), _) => candidates
+                    .iter()
+                    .find(|account| account.matches(query))
+                    .unwrap_or_else(|| {
+                        fail(&format!("no account with {} matches '{query}'", game.name))
+                    }),
+                (None, []) => fail(&format!("no account has {}", game.name)),
+                (None, [account]) => account,
+                (None, _) => {
+                    let names: Vec<String> = candidates.iter().map(|a| a.display_name()).collect();
+                    fail(&format!(
+                        "several accounts have {} ({}); pick one with --account",
+                        game.name,
+                        names.join(", ")
+                    ))
+                }
+            };
 
-    fn open_selected(&mut self) {
-        if let Some(i) = self.state.selected() {
-            let game = &self.filtered_items[i];
-            if game.path.exists() {
-                let _ = Command::new("xdg-open").arg(&game.path).spawn();
-                self.status_message = if game.is_non_steam {
-                    "Opened prefix folder.".to_string()
-                } else {
-                    "Opened game folder.".to_string()
-                };
+            let mut options = options.unwrap_or_else(|| {
+                find_launch_options(game, std::slice::from_ref(account))
+                    .pop()
+                    .map(|current| current.options)
+                    .unwrap_or_default()
+            });
+            for name in presets {
+                if let Some(preset) = LAUNCH_PRESETS.iter().find(|preset| preset.name == name) {
+                    options = toggle_preset(&options, preset);
+                }
+            }
+            let edit = set_launch_options(game, account, &options)?;
+            if edit.is_unchanged() {
+                println!(
+                    "The launch options of {} for {} are already '{}'.",
+                    game.name,
+                    account.display_name(),
+                    edit.new
+                );
+            } else if dry_run {
+                println!("{}\n- {}\n+ {}", edit.path.display(), edit.old, edit.new);
             } else {
-                self.status_message = "Folder does not exist.".to_string();
+                require_steam_stopped(force, "would overwrite the change when it exits");
+                let done = format!(
+                    "Set the launch options of {} for {} to '{}'.",
+                    game.name,
+                    account.display_name(),
+                    edit.new
+                );
+                match edit.apply() {
+                    Ok(Some(backup)) => {
+                        println!("{done} The original is saved as {}.", backup.display())
+                    }
+                    Ok(None) => println!("{done}"),
+                    Err(error) => fail(&error.to_string()),
+                }
             }
         }
-    }
-}
-
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let steam_dir = SteamDir::locate()?;
-    let compat_tools = steam_dir.compat_tool_mapping()?;
-    let mut items = Vec::new();
-
-    // Add Steam games
-    if let Ok(libraries_iter) = steam_dir.libraries() {
-        for folder in libraries_iter {
-            let folder = folder?;
-            for app_result in folder.apps() {
-                let app = app_result?;
-                if let Some(name) = app.name {
-                    items.push(Game {
-                        name,
-                        app_id: app.app_id,
-                        is_non_steam: false,
-                        path: app.install_dir.into(),
-                    });
+        Command::Userdata { query, format } => {
+            let config = load_config();
+            let game = &resolve(steam_dir, &config, &query)?;
+            let folders = find_userdata(game, &find_accounts(&game.installation));
+            if folders.is_empty() {
+                fail(&format!("no account has userdata for {}", game.name));
+            }
+            output::print_userdata(&folders, format)?;
+        }
+        Command::BackupSaves { query, keep } => {
+            let config = load_config();
+            let installations = choose_installations(steam_dir, &config, false);
+            let items = scan(&installations, &config)?;
+            let dir = config.save_backup_dir().unwrap_or_else(|| {
+                fail("set save_backup_dir in the config, or XDG_DATA_HOME or HOME")
+            });
+            let games: Vec<&Game> =
+                match &query {
+                    Some(query) => vec![output::resolve_game(&items, query)
+                        .unwrap_or_else(|message| fail(&message))],
+                    None => items.iter().collect(),
+                };
+            let keep = keep.unwrap_or(config.save_generations);
+            let mut saved = 0;
+            let mut failed = 0;
+            for game in games {
+                match back_up_saves(game, &dir, keep) {
+                    Ok(Some(backup)) => {
+                        saved += 1;
+                        println!(
+                            "Backed up {} save folders of {} to {}.",
+                            backup.folders.len(),
+                            game.name,
+                            backup.dir.display()
+                        );
+                    }
+                    Ok(None) if query.is_some() => println!("{} has no saves.", game.name),
+                    Ok(None) => {}
+                    Err(error) if query.is_some() => fail(&format!("{}: {error}", game.name)),
+                    // One broken prefix should not stop the backup of every other game
+                    Err(error) => {
+                        failed += 1;
+                        eprintln!("error: {}: {error}", game.name);
+                    }
                 }
             }
+            if query.is_none() {
+                println!("Backed up the saves of {saved} games.");
+            }
+            if failed > 0 {
+                fail(&format!("could not back up the saves of {failed} games"));
+            }
         }
-    }
-
-    // Add non-Steam games with Wine prefixes
-    for shortcut in steam_dir.shortcuts()? {
-        let shortcut = shortcut?;
-        if compat_tools.contains_key(&shortcut.app_id) {
-            let pfx_path = steam_dir
-                .path()
-                .join("steamapps")
-                .join("compatdata")
-                .join(format!("{}", shortcut.app_id))
-                .join("pfx");
-            items.push(Game {
-                name: shortcut.app_name,
-                app_id: shortcut.app_id,
-                is_non_steam: true,
-                path: pfx_path,
+        Command::SaveBackups { query, format } => {
+            let config = load_config();
+            let dir = config.save_backup_dir().unwrap_or_else(|| {
+                fail("set save_backup_dir in the config, or XDG_DATA_HOME or HOME")
             });
+            let mut backups = find_save_backups(&dir);
+            if let Some(query) = query {
+                backups.retain(|backup| match query.parse::<u32>() {
+                    Ok(app_id) => backup.app_id == app_id,
+                    Err(_) => fuzzy_match(&query, &backup.name).is_some(),
+                });
+            }
+            output::print_save_backups(&backups, format)?;
         }
-    }
+        Command::RestoreSaves {
+            query,
+            created,
+            yes,
+            force,
+        } => {
+            let config = load_config();
+            let game = &resolve(steam_dir, &config, &query)?;
+            let dir = config.save_backup_dir().unwrap_or_else(|| {
+                fail("set save_backup_dir in the config, or XDG_DATA_HOME or HOME")
+            });
+            let Some(backup) = find_save_backups(&dir).into_iter().find(|backup| {
+                backup.app_id == game.app_id && created.is_none_or(|time| backup.created == time)
+            }) else {
+                fail(&match created {
+                    Some(time) => format!("{} has no save backup made at {time}", game.name),
+                    None => format!("{} has no save backups", game.name),
+                });
+            };
+            require_steam_stopped(force, "could sync the saves while they are replaced");
+            println!(
+                "Restoring the saves of {} from {}:",
+                game.name,
+                output::format_timestamp(backup.created)
+            );
+            for folder in &backup.folders {
+                println!("  {}", folder.original.display());
+            }
+            if !yes && !confirm("Replace these folders?")? {
+                return Ok(());
+            }
+            match restore_saves(&backup) {
+                Ok(()) => println!("Restored {} save folders.", backup.folders.len()),
+                Err(error) => fail(&error.to_string()),
+            }
+        }
+        Command::Tui => {
+            let config = load_config();
+            let installations = choose_installations(steam_dir, &config, true);
+            let scanners = scanners(&installations, &config)?;
+            let items = visible_games(scan_all(&scanners)?, &config);
+            let installation_tools: Vec<(PathBuf, Vec<Tool>)> = scanners
+                .iter()
+                .map(|scanner| (scanner.steam_dir().path().to_path_buf(), scanner.tools()))
+                .collect();
+            let tools = merge_tools(&installation_tools);
+            let orphans = find_orphans(&scanners)?;
+
+            if items.is_empty() {
+                println!("No games found.");
+                return Ok(());
+            }
 
-    if items.is_empty() {
-        println!("No games found.");
-        return Ok(());
+            let opener = opener::Opener::from_env(&config.opener);
+            tui::run(
+                items,
+                tools,
+                installation_tools.into_iter().collect(),
+                orphans,
+                installations,
+                opener,
+                &config,
+            )?;
+        }
     }
 
-    // Setup terminal
-    enable_raw_mode()?;
-    let mut stdout = stdout();
-    execute!(stdout, EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+    Ok(())
+}
 
-    let mut app = App::new(items);
-    app.state.select(Some(0));
+fn fail(message: &str) -> ! {
+    eprintln!("error: {message}");
+    std::process::exit(1);
+}
 
-    loop {
-        terminal.draw(|f| {
-            let size = f.size();
-            let chunks = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints(
-                    [
-                        Constraint::Length(3),
-                        Constraint::Percentage(94),
-                        Constraint::Length(3),
-                    ]
-                    .as_ref(),
-                )
-                .split(size);
+/// Exits with an error while Steam is running unless `force` is set. `reason`
+/// says what Steam could do, e.g. "may be using the shader cache".
+fn require_steam_stopped(force: bool, reason: &str) {
+    if steam_running() && !force {
+        fail(&format!(
+            "Steam is running and {reason}; quit Steam first or pass --force"
+        ));
+    }
+}
 
-            let search_title = if app.in_search_mode {
-                "Search (type to search, Enter to exit)"
-            } else {
-                "Search (press '/' to enter search mode)"
-            };
-            let search_block = Block::default().borders(Borders::ALL).title(search_title);
-            let search_text = if app.search_query.is_empty() && !app.in_search_mode {
-                "No search query"
-            } else {
-                &app.search_query
-            };
-            let search_paragraph = Paragraph::new(search_text)
-                .block(search_block)
-                .style(Style::default().fg(Color::White));
+/// Asks `question` on the terminal and returns whether the answer was yes.
+fn confirm(question: &str) -> std::io::Result<bool> {
+    print!("{question} [y/N] ");
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
 
-            let list_items: Vec<ListItem> = app
-                .filtered_items
-                .iter()
-                .map(|game| {
-                    let label = if game.is_non_steam { "Non-Steam: " } else { "" };
-                    ListItem::new(Span::styled(
-                        format!("{}{} (App ID: {})", label, game.name, game.app_id),
-                        Style::default().fg(Color::White),
-                    ))
-                })
-                .collect();
+/// Loads the config file, exiting with its validation error when it is invalid.
+fn load_config() -> Config {
+    Config::load().unwrap_or_else(|error| fail(&error.to_string()))
+}
 
-            let list_title = format!(
-                "Games ({}/{}, ↑/↓ to navigate, Enter to open, q to quit)",
-                app.filtered_items.len(),
-                app.items.len()
-            );
-            let list = List::new(list_items)
-                .block(Block::default().borders(Borders::ALL).title(list_title))
-                .highlight_style(Style::default().bg(Color::Blue))
-                .highlight_symbol(">> ");
+/// Picks the Steam installations to scan: `--steam-dir` or its environment
+/// variable, then `steam_root` from the config, then every installation found
+/// on this machine. When several are found, `interactive` asks which to use.
+fn choose_installations(
+    steam_dir: Option<&Path>,
+    config: &Config,
+    interactive: bool,
+) -> Vec<Installation> {
+    let detected = find_installations();
+    if let Some(root) = steam_dir.or(config.steam_root.as_deref()) {
+        if !is_steam_root(root) {
+            fail(&format!(
+                "{} is not a Steam installation (it has no steamapps folder)",
+                root.display()
+            ));
+        }
+        let kind = detected
+            .into_iter()
+            .find(|installation| installation.path == root)
+            .map_or(InstallKind::Other, |installation| installation.kind);
+        return vec![Installation {
+            path: root.to_path_buf(),
+            kind,
+        }];
+    }
 
-            let footer = Paragraph::new(app.status_message.as_str())
-                .block(Block::default().borders(Borders::ALL))
-                .style(Style::default().fg(Color::Gray));
+    if detected.is_empty() {
+        fail(
+            "could not find a Steam installation; pass --steam-dir PATH or set \
+             steam_root in the config file",
+        );
+    }
+    if detected.len() > 1 && interactive {
+        match tui::pick_installations(&detected) {
+            Ok(Some(chosen)) => return chosen,
+            Ok(None) => std::process::exit(0),
+            Err(error) => fail(&error.to_string()),
+        }
+    }
+    detected
+}
 
-            f.render_widget(search_paragraph, chunks[0]);
-            f.render_stateful_widget(list, chunks[1], &mut app.state);
-            f.render_widget(footer, chunks[2]);
-        })?;
+/// Scans the chosen installations and resolves `query` to one game, exiting
+/// when it matches none or several.
+fn resolve(steam_dir: Option<&Path>, config: &Config, query: &str) -> steam_locater::Result<Game> {
+    let installations = choose_installations(steam_dir, config, false);
+    let items = scan(&installations, config)?;
+    let game = output::resolve_game(&items, query).unwrap_or_else(|message| fail(&message));
+    Ok(game.clone())
+}
 
-        if crossterm::event::poll(std::time::Duration::from_millis(100))? {
-            if let crossterm::event::Event::Key(key) = crossterm::event::read()? {
-                if app.in_search_mode {
-                    match key.code {
-                        crossterm::event::KeyCode::Enter => app.exit_search_mode(),
-                        crossterm::event::KeyCode::Backspace => {
-                            app.search_query.pop();
-                            app.update_filter();
-                        }
-                        crossterm::event::KeyCode::Char(c) => {
-                            app.search_query.push(c);
-                            app.update_filter();
-                        }
-                        _ => {}
-                    }
-                } else {
-                    match key.code {
-                        crossterm::event::KeyCode::Char('q') => break,
-                        crossterm::event::KeyCode::Char('/') => app.enter_search_mode(),
-                        crossterm::event::KeyCode::Down => app.next(),
-                        crossterm::event::KeyCode::Up => app.previous(),
-                        crossterm::event::KeyCode::Enter => app.open_selected(),
-                        _ => {}
-                    }
-                }
-            }
-        }
+/// Scans `installations` together. Extra libraries from the config are
+/// scanned once, alongside the first installation.
+fn scan(installations: &[Installation], config: &Config) -> steam_locater::Result<Vec<Game>> {
+    scan_all(&scanners(installations, config)?)
+}
+
+fn scanners(
+    installations: &[Installation],
+    config: &Config,
+) -> steam_locater::Result<Vec<Scanner>> {
+    let mut scanners = Vec::new();
+    for (index, installation) in installations.iter().enumerate() {
+        let scanner = Scanner::from_dir(&installation.path)?;
+        scanners.push(if index == 0 {
+            scanner.with_extra_libraries(config.extra_libraries.clone())
+        } else {
+            scanner
+        });
     }
+    Ok(scanners)
+}
 
-    // Restore terminal
-    disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-    terminal.show_cursor()?;
+/// The compatibility tools of every installation, each folder listed once.
+fn merge_tools(installation_tools: &[(PathBuf, Vec<Tool>)]) -> Vec<Tool> {
+    let mut tools: Vec<Tool> = Vec::new();
+    for tool in installation_tools
+        .iter()
+        .flat_map(|(_, tools)| tools)
+        .cloned()
+    {
+        match tools.iter_mut().find(|known| known.path == tool.path) {
+            Some(known) => known.default |= tool.default,
+            None => tools.push(tool),
+        }
+    }
+    tools
+}
 
-    Ok(())
+fn visible_games(games: Vec<Game>, config: &Config) -> Vec<Game> {
+    games
+        .into_iter()
+        .filter(|game| !config.hidden_games.contains(&game.app_id))
+        .collect()
 }
@@ -0,0 +1,221 @@
+use std::collections::HashSet;
+use std::io;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Environment variable holding an opener command tried before the defaults.
+pub const OPENER_ENV: &str = "STEAM_LOCATER_OPENER";
+
+/// Openers tried, in order, when none is configured or the configured one is missing.
+const DEFAULT_OPENERS: [&str; 3] = ["xdg-open {path}", "gio open {path}", "open {path}"];
+
+/// How long to wait for an opener to fail before assuming it started fine.
+const EXIT_GRACE: Duration = Duration::from_millis(500);
+
+/// Opens folders with the first available command of a fallback chain.
+///
+/// Each command is split on whitespace, honouring double and single quotes. The
+/// argument `{path}` is replaced by the folder; without one the folder is appended.
+pub struct Opener {
+    commands: Vec<String>,
+}
+
+impl Opener {
+    pub fn new(commands: Vec<String>) -> Self {
+        let mut seen = HashSet::new();
+        let commands = commands
+            .into_iter()
+            .chain(DEFAULT_OPENERS.map(str::to_string))
+            .filter(|command| seen.insert(command.clone()))
+            .collect();
+        Self { commands }
+    }
+
+    /// Tries the command in [`OPENER_ENV`], if set, then `configured`, then the defaults.
+    pub fn from_env(configured: &[String]) -> Self {
+        let from_env = std::env::var(OPENER_ENV)
+            .ok()
+            .filter(|command| !command.trim().is_empty());
+        Self::new(from_env.into_iter().chain(configured.to_vec()).collect())
+    }
+
+    /// Opens `path` on another thread, so waiting for an opener to fail does
+    /// not block the caller. The receiver gets the program used or a message
+    /// describing the failure.
+    pub fn open(&self, path: &Path) -> Receiver<Result<String, String>> {
+        let (sender, receiver) = mpsc::channel();
+        let commands = self.commands.clone();
+        let path = path.to_path_buf();
+        thread::spawn(move || sender.send(open_with(&commands, &path)));
+        receiver
+    }
+}
+
+/// Tries `commands` in order until one opens `path`.
+fn open_with(commands: &[String], path: &Path) -> Result<String, String> {
+    let mut missing = Vec::new();
+    for command in commands {
+        let Some((program, args)) = build_args(command, path) else {
+            continue;
+        };
+        let child = Command::new(&program)
+            .args(&args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                missing.push(program);
+                continue;
+            }
+            Err(error) => return Err(format!("Could not run {program}: {error}")),
+        };
+
+        let started = Instant::now();
+        loop {
+            match child.try_wait() {
+                Ok(Some(status)) if status.success() => return Ok(program),
+                Ok(Some(status)) => return Err(format!("{program} failed ({status}).")),
+                Ok(None) if started.elapsed() < EXIT_GRACE => {
+                    thread::sleep(Duration::from_millis(20))
+                }
+                Ok(None) => {
+                    // Still running, e.g. a file manager; reap it whenever it exits
+                    thread::spawn(move || child.wait());
+                    return Ok(program);
+                }
+                Err(error) => return Err(format!("Could not wait for {program}: {error}")),
+            }
+        }
+    }
+    Err(format!("No opener found (tried {}).", missing.join(", ")))
+}
+
+/// Splits `command` into a program and its arguments with `{path}` filled in.
+fn build_args(command: &str, path: &Path) -> Option<(String, Vec<String>)> {
+    let path = path.to_string_lossy();
+    let mut words = split_words(command);
+    if !words.iter().any(|word| word.contains("{path}")) {
+        words.push("{path}".to_string());
+    }
+    let mut words = words.into_iter().map(|word| word.replace("{path}", &path));
+    let program = words.next()?;
+    Some((program, words.collect()))
+}
+
+fn split_words(command: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote = None;
+    for c in command.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => word.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_quoted_words() {
+        assert_eq!(
+            split_words(r#"flatpak run  "org.gnome.Nautilus" --new-window '{path}'"#),
+            [
+                "flatpak",
+                "run",
+                "org.gnome.Nautilus",
+                "--new-window",
+                "{path}"
+            ]
+        );
+        assert_eq!(
+            split_words(r#"my-opener "two words" it's"#),
+            ["my-opener", "two words", "its"]
+        );
+        assert_eq!(split_words(r#"opener """#), ["opener", ""]);
+    }
+
+    #[test]
+    fn fills_in_the_path() {
+        let path = Path::new("/games/Dark Souls");
+        assert_eq!(
+            build_args("dolphin --select {path}", path),
+            Some((
+                "dolphin".to_string(),
+                vec!["--select".to_string(), "/games/Dark Souls".to_string()]
+            ))
+        );
+        assert_eq!(
+            build_args("thunar", path),
+            Some(("thunar".to_string(), vec!["/games/Dark Souls".to_string()]))
+        );
+        assert_eq!(
+            build_args("code --folder-uri=file://{path}", path),
+            Some((
+                "code".to_string(),
+                vec!["--folder-uri=file:///games/Dark Souls".to_string()]
+            ))
+        );
+    }
+
+    #[test]
+    fn tries_configured_openers_first() {
+        let opener = Opener::new(vec![
+            "thunar".to_string(),
+            "gio open {path}".to_string(),
+            "thunar".to_string(),
+        ]);
+        assert_eq!(
+            opener.commands,
+            [
+                "thunar",
+                "gio open {path}",
+                "xdg-open {path}",
+                "open {path}"
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn falls_back_past_missing_openers() {
+        let missing = format!("steam-locater-missing-opener-{}", std::process::id());
+        let path = Path::new("/");
+        assert_eq!(
+            open_with(&[missing.clone(), "true".to_string()], path),
+            Ok("true".to_string())
+        );
+        assert!(open_with(&["false".to_string(), "true".to_string()], path).is_err());
+        assert_eq!(
+            open_with(std::slice::from_ref(&missing), path),
+            Err(format!("No opener found (tried {missing})."))
+        );
+    }
+}
@@ -0,0 +1,154 @@
+//! Prefixes and shader caches left behind by games that are no longer installed.
+
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use serde::Serialize;
+
+use crate::files::unix_now;
+use crate::{create_archive, Result, Scanner};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrphanKind {
+    /// A `compatdata/<app_id>` folder holding a prefix.
+    CompatData,
+    /// A `shadercache/<app_id>` folder.
+    ShaderCache,
+}
+
+impl OrphanKind {
+    pub const ALL: [Self; 2] = [Self::CompatData, Self::ShaderCache];
+
+    /// Name of the folder under `steamapps` holding this kind of folder.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::CompatData => "compatdata",
+            Self::ShaderCache => "shadercache",
+        }
+    }
+}
+
+/// A per-app folder whose app is neither installed nor a shortcut of any
+/// account.
+#[derive(Clone, Debug, Serialize)]
+pub struct Orphan {
+    pub app_id: u32,
+    pub kind: OrphanKind,
+    pub path: PathBuf,
+    /// When the folder was last used, in seconds since the Unix epoch.
+    pub modified: Option<u64>,
+}
+
+/// Finds orphaned prefixes and shader caches in every library of `scanners`.
+///
+/// Installed apps and shortcuts of all the installations are taken into
+/// account, so a folder in a library shared by two installations is only an
+/// orphan when neither of them uses it.
+pub fn find_orphans(scanners: &[Scanner]) -> Result<Vec<Orphan>> {
+    let mut known = HashSet::new();
+    let mut libraries = Vec::new();
+    for scanner in scanners {
+        known.extend(scanner.known_app_ids()?);
+        for path in scanner.library_paths() {
+            let real_path = path.canonicalize().unwrap_or(path);
+            if !libraries.contains(&real_path) {
+                libraries.push(real_path);
+            }
+        }
+    }
+
+    let mut orphans = Vec::new();
+    for library in &libraries {
+        for kind in OrphanKind::ALL {
+            let Ok(entries) = fs::read_dir(library.join("steamapps").join(kind.as_str())) else {
+                continue;
+            };
+            let mut found: Vec<Orphan> = entries
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| {
+                    let app_id = entry.file_name().to_str()?.parse().ok()?;
+                    let path = entry.path();
+                    (app_id != 0 && !known.contains(&app_id) && path.is_dir()).then(|| Orphan {
+                        app_id,
+                        kind,
+                        modified: last_used(&path),
+                        path,
+                    })
+                })
+                .collect();
+            found.sort_by_key(|orphan| orphan.app_id);
+            orphans.extend(found);
+        }
+    }
+    Ok(orphans)
+}
+
+/// Deletes an orphaned folder and everything in it.
+pub fn delete_orphan(orphan: &Orphan) -> io::Result<()> {
+    fs::remove_dir_all(&orphan.path)
+}
+
+/// Packs an orphaned folder into `dir` as
+/// `<compatdata or shadercache>-<app_id>-<unix time>.tar.gz`, then deletes it.
+/// Returns the archive's path.
+pub fn archive_orphan(orphan: &Orphan, dir: &Path) -> io::Result<PathBuf> {
+    let secs = unix_now();
+    let archive = dir.join(format!(
+        "{}-{}-{secs}.tar.gz",
+        orphan.kind.as_str(),
+        orphan.app_id
+    ));
+    create_archive(&orphan.path, &archive)?;
+    delete_orphan(orphan)?;
+    Ok(archive)
+}
+
+/// The newest modification time of the folder and the files directly in it,
+/// or for a prefix of `pfx/user.reg`, which Wine rewrites whenever the game
+/// runs.
+fn last_used(dir: &Path) -> Option<u64> {
+    let pfx = dir.join("pfx");
+    let mut paths = vec![dir.to_path_buf(), pfx.join("user.reg"), pfx];
+    if let Ok(entries) = fs::read_dir(dir) {
+        paths.extend(entries.filter_map(|entry| Some(entry.ok()?.path())));
+    }
+    paths
+        .iter()
+        .filter_map(|path| fs::metadata(path).ok()?.modified().ok())
+        .filter_map(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|since| since.as_secs())
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refuses_without_library_list() {
+        let root =
+            std::env::temp_dir().join(format!("steam-locater-orphans-{}", std::process::id()));
+        fs::create_dir_all(root.join("steamapps").join("compatdata").join("1245620")).unwrap();
+        let scanner = Scanner::from_dir(&root).unwrap();
+        assert!(find_orphans(&[scanner]).is_err());
+
+        let library_folders = format!(
+            "\"libraryfolders\"\n{{\n\t\"0\"\n\t{{\n\t\t\"path\"\t\t\"{}\"\n\t\t\"apps\"\n\t\t{{\n\t\t}}\n\t}}\n}}\n",
+            root.display()
+        );
+        fs::write(
+            root.join("steamapps").join("libraryfolders.vdf"),
+            library_folders,
+        )
+        .unwrap();
+        let scanner = Scanner::from_dir(&root).unwrap();
+        let orphans = find_orphans(&[scanner]).unwrap();
+        let app_ids: Vec<u32> = orphans.iter().map(|orphan| orphan.app_id).collect();
+        assert_eq!(app_ids, [1_245_620]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
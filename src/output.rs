@@ -0,0 +1,557 @@
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use steam_locater::{
+    dir_size, Account, Backup, Folder, Game, LaunchOptions, Orphan, SaveBackup, SaveLocation,
+    UserdataFolder,
+};
+
+use crate::cli::Format;
+
+const HEADERS: [&str; 8] = [
+    "name",
+    "app_id",
+    "kind",
+    "size",
+    "compat_tool",
+    "install_path",
+    "prefix_path",
+    "owners",
+];
+
+pub fn print_games(games: &[Game], format: Format) -> io::Result<()> {
+    let mut out = io::stdout().lock();
+    match format {
+        Format::Table => {
+            let rows: Vec<[String; 8]> = games
+                .iter()
+                .map(|game| {
+                    [
+                        game.name.clone(),
+                        game.app_id.to_string(),
+                        game.kind.as_str().to_string(),
+                        game.size_on_disk.map(format_size).unwrap_or_default(),
+                        match &game.compat_tool {
+                            Some(tool) if game.compat_tool_missing() => {
+                                format!("{tool} (missing)")
+                            }
+                            Some(tool) => tool.clone(),
+                            None => String::new(),
+                        },
+                        game.install_path.display().to_string(),
+                        display_path(game.prefix_path.as_deref()),
+                        game.owner_names(),
+                    ]
+                })
+                .collect();
+            let header = HEADERS.map(str::to_uppercase);
+            write_rows(&mut out, header.each_ref().map(String::as_str), &rows)
+        }
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut out, games)?;
+            writeln!(out)
+        }
+        Format::Csv => {
+            let rows: Vec<[String; 8]> = games
+                .iter()
+                .map(|game| {
+                    [
+                        game.name.clone(),
+                        game.app_id.to_string(),
+                        game.kind.as_str().to_string(),
+                        game.size_on_disk
+                            .map(|size| size.to_string())
+                            .unwrap_or_default(),
+                        game.compat_tool.clone().unwrap_or_default(),
+                        game.install_path.display().to_string(),
+                        display_path(game.prefix_path.as_deref()),
+                        game.owner_names(),
+                    ]
+                })
+                .collect();
+            write_csv_rows(&mut out, HEADERS, &rows)
+        }
+    }
+}
+
+/// An orphan with its measured size, as printed by `orphans`.
+#[derive(Serialize)]
+struct OrphanRecord<'a> {
+    #[serde(flatten)]
+    orphan: &'a Orphan,
+    size: u64,
+}
+
+pub fn print_orphans(orphans: &[(Orphan, u64)], format: Format) -> io::Result<()> {
+    let mut out = io::stdout().lock();
+    match format {
+        Format::Table => {
+            let rows: Vec<[String; 5]> = orphans
+                .iter()
+                .map(|(orphan, size)| {
+                    [
+                        orphan.app_id.to_string(),
+                        orphan.kind.as_str().to_string(),
+                        format_size(*size),
+                        orphan.modified.map(format_timestamp).unwrap_or_default(),
+                        orphan.path.display().to_string(),
+                    ]
+                })
+                .collect();
+            write_rows(
+                &mut out,
+                ["APP_ID", "KIND", "SIZE", "LAST_USED", "PATH"],
+                &rows,
+            )?;
+            let total: u64 = orphans.iter().map(|(_, size)| size).sum();
+            writeln!(
+                out,
+                "\n{} orphaned folders, {}",
+                orphans.len(),
+                format_size(total)
+            )
+        }
+        Format::Json => {
+            let records: Vec<OrphanRecord> = orphans
+                .iter()
+                .map(|(orphan, size)| OrphanRecord {
+                    orphan,
+                    size: *size,
+                })
+                .collect();
+            serde_json::to_writer_pretty(&mut out, &records)?;
+            writeln!(out)
+        }
+        Format::Csv => {
+            let rows: Vec<[String; 5]> = orphans
+                .iter()
+                .map(|(orphan, size)| {
+                    [
+                        orphan.app_id.to_string(),
+                        orphan.kind.as_str().to_string(),
+                        size.to_string(),
+                        orphan
+                            .modified
+                            .map(|secs| secs.to_string())
+                            .unwrap_or_default(),
+                        orphan.path.display().to_string(),
+                    ]
+                })
+                .collect();
+            write_csv_rows(
+                &mut out,
+                ["app_id", "kind", "size", "modified", "path"],
+                &rows,
+            )
+        }
+    }
+}
+
+/// A backup with the size of its tarball, as printed by `backups`.
+#[derive(Serialize)]
+struct BackupRecord<'a> {
+    #[serde(flatten)]
+    backup: &'a Backup,
+    size: u64,
+}
+
+pub fn print_backups(backups: &[Backup], format: Format) -> io::Result<()> {
+    let mut out = io::stdout().lock();
+    let size = |backup: &Backup| std::fs::metadata(&backup.archive).map_or(0, |m| m.len());
+    match format {
+        Format::Table => {
+            let rows: Vec<[String; 6]> = backups
+                .iter()
+                .map(|backup| {
+                    [
+                        backup.created.to_string(),
+                        format_timestamp(backup.created),
+                        backup.app_id.to_string(),
+                        backup.name.clone(),
+                        backup.compat_tool.clone().unwrap_or_default(),
+                        format_size(size(backup)),
+                    ]
+                })
+                .collect();
+            write_rows(
+                &mut out,
+                ["CREATED", "DATE", "APP_ID", "NAME", "COMPAT_TOOL", "SIZE"],
+                &rows,
+            )
+        }
+        Format::Json => {
+            let records: Vec<BackupRecord> = backups
+                .iter()
+                .map(|backup| BackupRecord {
+                    backup,
+                    size: size(backup),
+                })
+                .collect();
+            serde_json::to_writer_pretty(&mut out, &records)?;
+            writeln!(out)
+        }
+        Format::Csv => {
+            let rows: Vec<[String; 6]> = backups
+                .iter()
+                .map(|backup| {
+                    [
+                        backup.created.to_string(),
+                        backup.app_id.to_string(),
+                        backup.name.clone(),
+                        backup.compat_tool.clone().unwrap_or_default(),
+                        size(backup).to_string(),
+                        backup.archive.display().to_string(),
+                    ]
+                })
+                .collect();
+            write_csv_rows(
+                &mut out,
+                [
+                    "created",
+                    "app_id",
+                    "name",
+                    "compat_tool",
+                    "size",
+                    "archive",
+                ],
+                &rows,
+            )
+        }
+    }
+}
+
+pub fn print_save_backups(backups: &[SaveBackup], format: Format) -> io::Result<()> {
+    let mut out = io::stdout().lock();
+    match format {
+        Format::Table => {
+            let rows: Vec<[String; 6]> = backups
+                .iter()
+                .map(|backup| {
+                    [
+                        backup.created.to_string(),
+                        format_timestamp(backup.created),
+                        backup.app_id.to_string(),
+                        backup.name.clone(),
+                        backup.folders.len().to_string(),
+                        format_size(dir_size(&backup.dir)),
+                    ]
+                })
+                .collect();
+            write_rows(
+                &mut out,
+                ["CREATED", "DATE", "APP_ID", "NAME", "FOLDERS", "SIZE"],
+                &rows,
+            )
+        }
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut out, backups)?;
+            writeln!(out)
+        }
+        Format::Csv => {
+            let rows: Vec<[String; 6]> = backups
+                .iter()
+                .map(|backup| {
+                    [
+                        backup.created.to_string(),
+                        backup.app_id.to_string(),
+                        backup.name.clone(),
+                        backup.folders.len().to_string(),
+                        dir_size(&backup.dir).to_string(),
+                        backup.dir.display().to_string(),
+                    ]
+                })
+                .collect();
+            write_csv_rows(
+                &mut out,
+                ["created", "app_id", "name", "folders", "size", "dir"],
+                &rows,
+            )
+        }
+    }
+}
+
+pub fn print_saves(locations: &[SaveLocation], format: Format) -> io::Result<()> {
+    let mut out = io::stdout().lock();
+    match format {
+        Format::Table => {
+            let rows: Vec<[String; 3]> = locations
+                .iter()
+                .map(|location| {
+                    [
+                        location.modified.map(format_timestamp).unwrap_or_default(),
+                        format_size(location.size),
+                        location.path.display().to_string(),
+                    ]
+                })
+                .collect();
+            write_rows(&mut out, ["MODIFIED", "SIZE", "PATH"], &rows)
+        }
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut out, locations)?;
+            writeln!(out)
+        }
+        Format::Csv => {
+            let rows: Vec<[String; 4]> = locations
+                .iter()
+                .map(|location| {
+                    [
+                        location.root.to_string(),
+                        location.size.to_string(),
+                        location
+                            .modified
+                            .map(|secs| secs.to_string())
+                            .unwrap_or_default(),
+                        location.path.display().to_string(),
+                    ]
+                })
+                .collect();
+            write_csv_rows(&mut out, ["root", "size", "modified", "path"], &rows)
+        }
+    }
+}
+
+pub fn print_accounts(accounts: &[Account], format: Format) -> io::Result<()> {
+    let mut out = io::stdout().lock();
+    match format {
+        Format::Table => {
+            let rows: Vec<[String; 5]> = accounts
+                .iter()
+                .map(|account| {
+                    [
+                        account.account_id.to_string(),
+                        account.steam_id.to_string(),
+                        account.account_name.clone().unwrap_or_default(),
+                        account.persona_name.clone().unwrap_or_default(),
+                        if account.most_recent { "yes" } else { "" }.to_string(),
+                    ]
+                })
+                .collect();
+            write_rows(
+                &mut out,
+                ["ACCOUNT_ID", "STEAM_ID", "LOGIN", "PERSONA", "MOST_RECENT"],
+                &rows,
+            )
+        }
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut out, accounts)?;
+            writeln!(out)
+        }
+        Format::Csv => {
+            let rows: Vec<[String; 5]> = accounts
+                .iter()
+                .map(|account| {
+                    [
+                        account.account_id.to_string(),
+                        account.steam_id.to_string(),
+                        account.account_name.clone().unwrap_or_default(),
+                        account.persona_name.clone().unwrap_or_default(),
+                        account.most_recent.to_string(),
+                    ]
+                })
+                .collect();
+            write_csv_rows(
+                &mut out,
+                ["account_id", "steam_id", "login", "persona", "most_recent"],
+                &rows,
+            )
+        }
+    }
+}
+
+pub fn print_userdata(folders: &[UserdataFolder], format: Format) -> io::Result<()> {
+    let mut out = io::stdout().lock();
+    match format {
+        Format::Table => {
+            let rows: Vec<[String; 4]> = folders
+                .iter()
+                .map(|folder| {
+                    [
+                        folder.account.display_name(),
+                        folder.kind.as_str().to_string(),
+                        format_size(dir_size(&folder.path)),
+                        folder.path.display().to_string(),
+                    ]
+                })
+                .collect();
+            write_rows(&mut out, ["ACCOUNT", "KIND", "SIZE", "PATH"], &rows)
+        }
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut out, folders)?;
+            writeln!(out)
+        }
+        Format::Csv => {
+            let rows: Vec<[String; 5]> = folders
+                .iter()
+                .map(|folder| {
+                    [
+                        folder.account.account_id.to_string(),
+                        folder.account.display_name(),
+                        folder.kind.as_str().to_string(),
+                        dir_size(&folder.path).to_string(),
+                        folder.path.display().to_string(),
+                    ]
+                })
+                .collect();
+            write_csv_rows(
+                &mut out,
+                ["account_id", "account", "kind", "size", "path"],
+                &rows,
+            )
+        }
+    }
+}
+
+pub fn print_launch_options(options: &[LaunchOptions], format: Format) -> io::Result<()> {
+    let mut out = io::stdout().lock();
+    match format {
+        Format::Table => {
+            let rows: Vec<[String; 2]> = options
+                .iter()
+                .map(|options| [options.account.display_name(), options.options.clone()])
+                .collect();
+            write_rows(&mut out, ["ACCOUNT", "LAUNCH_OPTIONS"], &rows)
+        }
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut out, options)?;
+            writeln!(out)
+        }
+        Format::Csv => {
+            let rows: Vec<[String; 3]> = options
+                .iter()
+                .map(|options| {
+                    [
+                        options.account.account_id.to_string(),
+                        options.account.display_name(),
+                        options.options.clone(),
+                    ]
+                })
+                .collect();
+            write_csv_rows(&mut out, ["account_id", "account", "launch_options"], &rows)
+        }
+    }
+}
+
+/// Resolves `query` to a single game, explaining when it matches none or several.
+pub fn resolve_game<'a>(games: &'a [Game], query: &str) -> Result<&'a Game, String> {
+    match steam_locater::find_games(games, query)[..] {
+        [] => Err(format!("no game matches '{query}'")),
+        [game] => Ok(game),
+        ref matches => {
+            let candidates: Vec<String> = matches
+                .iter()
+                .map(|game| format!("  {} ({})", game.name, game.app_id))
+                .collect();
+            Err(format!(
+                "'{query}' matches {} games, use an app ID:\n{}",
+                matches.len(),
+                candidates.join("\n")
+            ))
+        }
+    }
+}
+
+/// The game's `folder`, when it has one and it exists.
+pub fn existing_folder(game: &Game, folder: Folder) -> Result<PathBuf, String> {
+    match game.folder(folder) {
+        Some(path) if path.exists() => Ok(path),
+        Some(path) => Err(format!(
+            "{} of {} does not exist: {}",
+            folder.describe(),
+            game.name,
+            path.display()
+        )),
+        None => Err(format!("{} has no {}", game.name, folder.describe())),
+    }
+}
+
+/// Formats a byte count with binary units, e.g. `1.5 GiB`.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+/// Formats seconds since the Unix epoch as a UTC date and time.
+pub fn format_timestamp(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let time = secs % 86_400;
+
+    // Civil date from days since 1970-01-01, valid for the proleptic Gregorian calendar
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02} UTC",
+        time / 3600,
+        time % 3600 / 60
+    )
+}
+
+fn display_path(path: Option<&Path>) -> String {
+    path.map(|path| path.display().to_string())
+        .unwrap_or_default()
+}
+
+/// Prints `rows` under `header` as a table with columns padded to line up.
+fn write_rows<const N: usize>(
+    out: &mut impl Write,
+    header: [&str; N],
+    rows: &[[String; N]],
+) -> io::Result<()> {
+    let mut widths = header.map(|cell| cell.chars().count());
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let header = header.map(str::to_string);
+    for row in std::iter::once(&header).chain(rows) {
+        let line: Vec<String> = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect();
+        writeln!(out, "{}", line.join("  ").trim_end())?;
+    }
+    Ok(())
+}
+
+/// Prints `rows` under `header` as CSV, quoting fields where needed.
+fn write_csv_rows<const N: usize>(
+    out: &mut impl Write,
+    header: [&str; N],
+    rows: &[[String; N]],
+) -> io::Result<()> {
+    writeln!(out, "{}", header.join(","))?;
+    for row in rows {
+        let fields: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+        writeln!(out, "{}", fields.join(","))?;
+    }
+    Ok(())
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
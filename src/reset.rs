@@ -0,0 +1,65 @@
+//! Resetting a broken prefix by moving it to a trash folder, so Proton
+//! creates a fresh one the next time the game starts.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::files::{copy_dir, move_dir, unix_now};
+use crate::{dir_size, Folder, Game};
+
+/// The Wine user folder holding most saves and settings, relative to `pfx`.
+const SAVES_DIR: &str = "drive_c/users/steamuser";
+
+/// What [`reset_prefix`] moved and kept.
+#[derive(Clone, Debug, Serialize)]
+pub struct PrefixReset {
+    /// The `compatdata/<app_id>` folder that was reset.
+    pub compat_data: PathBuf,
+    /// Where the old folder now is.
+    pub trash: PathBuf,
+    /// The files and folders that were moved, as paths in `trash`.
+    pub moved: Vec<PathBuf>,
+    /// Bytes moved to the trash.
+    pub size: u64,
+    /// The save folder copied back into the new prefix, if any.
+    pub kept: Option<PathBuf>,
+}
+
+/// Moves the game's `compatdata/<app_id>` folder into `trash_dir` as
+/// `compatdata-<app_id>-<unix time>`, so nothing is lost if the reset was a
+/// mistake.
+///
+/// With `keep_saves`, `drive_c/users/steamuser` is copied back into an
+/// otherwise empty prefix, which Proton fills in around it.
+pub fn reset_prefix(game: &Game, trash_dir: &Path, keep_saves: bool) -> io::Result<PrefixReset> {
+    let Some(compat_data) = game.folder(Folder::CompatData) else {
+        return Err(io::Error::other(format!("{} has no prefix", game.name)));
+    };
+    let secs = unix_now();
+    let trash = trash_dir.join(format!("compatdata-{}-{secs}", game.app_id));
+    let mut moved: Vec<PathBuf> = fs::read_dir(&compat_data)?
+        .filter_map(|entry| Some(trash.join(entry.ok()?.file_name())))
+        .collect();
+    moved.sort();
+    let size = dir_size(&compat_data);
+    move_dir(&compat_data, &trash)?;
+
+    let saves = trash.join("pfx").join(SAVES_DIR);
+    let kept = if keep_saves && saves.is_dir() {
+        let dest = compat_data.join("pfx").join(SAVES_DIR);
+        copy_dir(&saves, &dest)?;
+        Some(dest)
+    } else {
+        None
+    };
+    Ok(PrefixReset {
+        compat_data,
+        trash,
+        moved,
+        size,
+        kept,
+    })
+}
@@ -0,0 +1,180 @@
+//! Versioned copies of a game's saves, from its prefix and from the Steam
+//! Cloud folders of every account.
+//!
+//! Each backup of app 1245620 is a generation folder like
+//! `<dir>/1245620/1700000000`, holding `prefix/<folder under steamuser>`,
+//! `userdata/<account id>` and a `saves.json` listing where each came from.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::edit::write_atomic;
+use crate::files::{copy_dir, unix_now};
+use crate::{find_save_locations, userdata, Game};
+
+/// Metadata file written into each generation folder.
+const METADATA: &str = "saves.json";
+
+/// One generation of a game's saves.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SaveBackup {
+    pub app_id: u32,
+    pub name: String,
+    /// When the backup was made, in seconds since the Unix epoch.
+    pub created: u64,
+    pub folders: Vec<SavedFolder>,
+    /// The generation folder. Found when loading, so a moved backup folder
+    /// keeps working.
+    #[serde(default)]
+    pub dir: PathBuf,
+}
+
+/// A save folder copied into a [`SaveBackup`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SavedFolder {
+    /// Where the copy is, relative to the generation folder.
+    pub stored: PathBuf,
+    /// Where it was copied from, and is restored to.
+    pub original: PathBuf,
+}
+
+/// The save folders of `game`: those found in its prefix and the
+/// `userdata/<account id>/<app_id>/remote` folders of its installation, each
+/// with the path it is stored under in a backup.
+pub fn save_folders(game: &Game) -> Vec<SavedFolder> {
+    let user = game
+        .prefix_path
+        .as_ref()
+        .map(|pfx| pfx.join("drive_c/users/steamuser"));
+    let mut folders: Vec<SavedFolder> = find_save_locations(game)
+        .into_iter()
+        .filter_map(|location| {
+            let relative = location.path.strip_prefix(user.as_ref()?).ok()?;
+            Some(SavedFolder {
+                stored: Path::new("prefix").join(relative),
+                original: location.path,
+            })
+        })
+        .collect();
+    folders.extend(
+        userdata::remote_dirs(&game.installation, game.app_id)
+            .into_iter()
+            .map(|(account_id, dir)| SavedFolder {
+                stored: Path::new("userdata").join(account_id.to_string()),
+                original: dir,
+            }),
+    );
+    folders
+}
+
+/// Copies the saves of `game` into a new generation folder under `dir`, then
+/// deletes its oldest generations so at most `generations` are kept.
+///
+/// Returns `None` without writing anything when the game has no saves.
+pub fn back_up_saves(
+    game: &Game,
+    dir: &Path,
+    generations: usize,
+) -> io::Result<Option<SaveBackup>> {
+    let folders = save_folders(game);
+    if folders.is_empty() {
+        return Ok(None);
+    }
+    let game_dir = dir.join(game.app_id.to_string());
+    fs::create_dir_all(&game_dir)?;
+    let mut created = unix_now();
+    // Two backups in the same second get consecutive times
+    while game_dir.join(created.to_string()).exists() {
+        created += 1;
+    }
+    let backup = SaveBackup {
+        app_id: game.app_id,
+        name: game.name.clone(),
+        created,
+        folders,
+        dir: game_dir.join(created.to_string()),
+    };
+
+    let result = backup
+        .folders
+        .iter()
+        .try_for_each(|folder| copy_dir(&folder.original, &backup.dir.join(&folder.stored)))
+        .and_then(|()| serde_json::to_string_pretty(&backup).map_err(io::Error::other))
+        .and_then(|metadata| write_atomic(&backup.dir.join(METADATA), metadata.as_bytes()));
+    if let Err(error) = result {
+        let _ = fs::remove_dir_all(&backup.dir);
+        return Err(error);
+    }
+
+    for old in find_save_backups(dir)
+        .into_iter()
+        .filter(|old| old.app_id == game.app_id)
+        .skip(generations.max(1))
+    {
+        fs::remove_dir_all(&old.dir)?;
+    }
+    Ok(Some(backup))
+}
+
+/// Every save backup in `dir`, newest first. Generations without a readable
+/// metadata file, such as unfinished ones, are skipped.
+pub fn find_save_backups(dir: &Path) -> Vec<SaveBackup> {
+    let Ok(games) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<SaveBackup> = games
+        .filter_map(|entry| fs::read_dir(entry.ok()?.path()).ok())
+        .flatten()
+        .filter_map(|entry| {
+            let dir = entry.ok()?.path();
+            let text = fs::read_to_string(dir.join(METADATA)).ok()?;
+            let mut backup: SaveBackup = serde_json::from_str(&text).ok()?;
+            backup.dir = dir;
+            Some(backup)
+        })
+        .collect();
+    backups.sort_by_key(|backup| std::cmp::Reverse(backup.created));
+    backups
+}
+
+/// Copies every folder of `backup` back to where it came from, replacing the
+/// current saves there.
+///
+/// Each folder is copied next to its original first and swapped in with
+/// renames, so a failed copy leaves the current saves as they were.
+pub fn restore_saves(backup: &SaveBackup) -> io::Result<()> {
+    for folder in &backup.folders {
+        let staging = sibling(&folder.original, "steam-locater-restore");
+        let old = sibling(&folder.original, "steam-locater-old");
+        let _ = fs::remove_dir_all(&staging);
+        if let Err(error) = copy_dir(&backup.dir.join(&folder.stored), &staging) {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(error);
+        }
+        let had_saves = folder.original.exists();
+        if had_saves {
+            fs::rename(&folder.original, &old)?;
+        }
+        if let Err(error) = fs::rename(&staging, &folder.original) {
+            if had_saves {
+                let _ = fs::rename(&old, &folder.original);
+            }
+            let _ = fs::remove_dir_all(&staging);
+            return Err(error);
+        }
+        if had_saves {
+            fs::remove_dir_all(&old)?;
+        }
+    }
+    Ok(())
+}
+
+/// `<path>.<suffix>` in the same folder.
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{suffix}"));
+    path.with_file_name(name)
+}
@@ -0,0 +1,109 @@
+//! Folders inside a prefix where Windows games usually keep their saves.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use serde::Serialize;
+
+use crate::Game;
+
+/// Folders under `drive_c/users/steamuser` whose subfolders hold saves.
+/// Older Proton versions use `My Documents` instead of `Documents`.
+const SAVE_ROOTS: [&str; 7] = [
+    "AppData/Roaming",
+    "AppData/Local",
+    "AppData/LocalLow",
+    "Documents/My Games",
+    "My Documents/My Games",
+    "Saved Games",
+    "Documents",
+];
+
+/// Folders that Wine or Windows create in every prefix.
+const IGNORED: [&str; 6] = [
+    "Microsoft",
+    "Temp",
+    "My Games",
+    "My Music",
+    "My Pictures",
+    "My Videos",
+];
+
+/// A folder in a prefix that likely holds saves.
+#[derive(Clone, Debug, Serialize)]
+pub struct SaveLocation {
+    pub path: PathBuf,
+    /// Where it was found, relative to `drive_c/users/steamuser`, e.g.
+    /// `AppData/Roaming`.
+    pub root: &'static str,
+    /// Total size of the files in it, in bytes.
+    pub size: u64,
+    /// When any file in it last changed, in seconds since the Unix epoch.
+    pub modified: Option<u64>,
+}
+
+/// Finds the non-empty subfolders of the usual save folders in the game's
+/// prefix, most recently changed first.
+pub fn find_save_locations(game: &Game) -> Vec<SaveLocation> {
+    let Some(pfx) = &game.prefix_path else {
+        return Vec::new();
+    };
+    let user = pfx.join("drive_c/users/steamuser");
+    let mut seen = Vec::new();
+    let mut locations = Vec::new();
+    for root in SAVE_ROOTS {
+        let Ok(entries) = fs::read_dir(user.join(root)) else {
+            continue;
+        };
+        for entry in entries.filter_map(Result::ok) {
+            let path = entry.path();
+            let ignored = entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| IGNORED.contains(&name));
+            // `My Documents` is often a link to `Documents`
+            let real_path = path.canonicalize().unwrap_or_else(|_| path.clone());
+            if ignored || !path.is_dir() || seen.contains(&real_path) {
+                continue;
+            }
+            seen.push(real_path);
+            let (size, modified) = walk(&path);
+            if size > 0 {
+                locations.push(SaveLocation {
+                    path,
+                    root,
+                    size,
+                    modified,
+                });
+            }
+        }
+    }
+    locations.sort_by_key(|location| std::cmp::Reverse(location.modified));
+    locations
+}
+
+/// Total size and newest modification time of the files under `path`.
+/// Symlinks are not followed.
+fn walk(path: &Path) -> (u64, Option<u64>) {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return (0, None);
+    };
+    if !metadata.is_dir() {
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|since| since.as_secs());
+        return (metadata.len(), modified);
+    }
+    let Ok(entries) = fs::read_dir(path) else {
+        return (0, None);
+    };
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| walk(&entry.path()))
+        .fold((0, None), |(size, newest), (entry_size, modified)| {
+            (size + entry_size, newest.max(modified))
+        })
+}
@@ -0,0 +1,269 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use steamlocate::{CompatTool, Library, SteamDir};
+
+use crate::{find_accounts, find_tools, resolve_tool, userdata, Game, GameKind, Result, Tool};
+
+/// Scans a Steam installation for games and their prefixes.
+pub struct Scanner {
+    steam_dir: SteamDir,
+    extra_libraries: Vec<PathBuf>,
+}
+
+impl Scanner {
+    /// Uses the Steam installation found by [`SteamDir::locate`].
+    pub fn locate() -> Result<Self> {
+        Ok(Self::new(SteamDir::locate()?))
+    }
+
+    /// Uses the Steam installation rooted at `path`.
+    pub fn from_dir(path: &Path) -> Result<Self> {
+        Ok(Self::new(SteamDir::from_dir(path)?))
+    }
+
+    pub fn new(steam_dir: SteamDir) -> Self {
+        Self {
+            steam_dir,
+            extra_libraries: Vec::new(),
+        }
+    }
+
+    /// Also scans these library folders, e.g. ones Steam does not know about.
+    pub fn with_extra_libraries(mut self, paths: Vec<PathBuf>) -> Self {
+        self.extra_libraries = paths;
+        self
+    }
+
+    pub fn steam_dir(&self) -> &SteamDir {
+        &self.steam_dir
+    }
+
+    /// Every library folder, always including the Steam root itself and any
+    /// extra libraries.
+    pub fn library_paths(&self) -> Vec<PathBuf> {
+        let mut library_paths = self.steam_dir.library_paths().unwrap_or_default();
+        let root = self.steam_dir.path().to_path_buf();
+        for path in std::iter::once(&root).chain(&self.extra_libraries) {
+            if !library_paths.contains(path) {
+                library_paths.push(path.clone());
+            }
+        }
+        library_paths
+    }
+
+    /// The compatibility tools available to this installation, with the one
+    /// set as Steam's default marked.
+    pub fn tools(&self) -> Vec<Tool> {
+        let mut tools = find_tools(self.steam_dir.path(), &self.library_paths());
+        // App ID 0 holds the tool chosen for all titles in Steam Play settings
+        let default = self
+            .steam_dir
+            .compat_tool_mapping()
+            .ok()
+            .and_then(|compat_tools| tool_name(&compat_tools, 0));
+        for tool in &mut tools {
+            tool.default = default.as_deref() == Some(tool.name.as_str());
+        }
+        tools
+    }
+
+    /// The libraries Steam knows about followed by the extra ones. Fails when
+    /// `libraryfolders.vdf` cannot be read, since every library would be missed.
+    fn libraries(&self) -> Result<Vec<Library>> {
+        let mut libraries = Vec::new();
+        for folder in self.steam_dir.libraries()? {
+            libraries.push(folder?);
+        }
+        for path in &self.extra_libraries {
+            if !libraries.iter().any(|library| library.path() == path) {
+                libraries.push(Library::from_dir(path)?);
+            }
+        }
+        Ok(libraries)
+    }
+
+    /// App IDs of every installed app, named or not, and of every non-Steam
+    /// shortcut of any account.
+    pub fn known_app_ids(&self) -> Result<HashSet<u32>> {
+        let mut app_ids = HashSet::new();
+        for library in self.libraries()? {
+            app_ids.extend(library.app_ids().iter().copied());
+            for app in library.apps() {
+                app_ids.insert(app?.app_id);
+            }
+        }
+        if self.steam_dir.path().join("userdata").is_dir() {
+            for shortcut in self.steam_dir.shortcuts()? {
+                app_ids.insert(shortcut?.app_id);
+            }
+        }
+        Ok(app_ids)
+    }
+
+    /// Lists installed Steam games followed by non-Steam shortcuts that use
+    /// a compatibility tool or have a prefix.
+    pub fn scan(&self) -> Result<Vec<Game>> {
+        let mut games = Vec::new();
+        self.scan_into(&mut games, &mut HashSet::new())?;
+        Ok(games)
+    }
+
+    /// Scans into `games`, skipping library folders already in `seen_libraries`
+    /// and adding the ones scanned to it.
+    fn scan_into(
+        &self,
+        games: &mut Vec<Game>,
+        seen_libraries: &mut HashSet<PathBuf>,
+    ) -> Result<()> {
+        // A fresh installation has no config.vdf yet
+        let compat_tools = self.steam_dir.compat_tool_mapping().unwrap_or_default();
+        let library_paths = self.library_paths();
+        let last_played = userdata::last_played(self.steam_dir.path());
+        let installation = self.steam_dir.path().to_path_buf();
+        let tools = self.tools();
+
+        for folder in &self.libraries()? {
+            let real_path = folder
+                .path()
+                .canonicalize()
+                .unwrap_or_else(|_| folder.path().to_path_buf());
+            if !seen_libraries.insert(real_path) {
+                continue;
+            }
+            for app_result in folder.apps() {
+                let app = app_result?;
+                if let Some(name) = app.name.clone() {
+                    games.push(Game {
+                        name,
+                        app_id: app.app_id,
+                        kind: GameKind::Steam,
+                        installation: installation.clone(),
+                        install_path: folder.resolve_app_dir(&app),
+                        prefix_path: find_prefix(&library_paths, app.app_id),
+                        compat_tool: tool_name(&compat_tools, app.app_id),
+                        compat_tool_path: tool_path(&tools, &compat_tools, app.app_id),
+                        library: Some(folder.path().to_path_buf()),
+                        size_on_disk: app.size_on_disk,
+                        shader_cache_path: find_shader_cache(&library_paths, app.app_id),
+                        build_id: app.build_id,
+                        last_updated: app
+                            .last_updated
+                            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                            .map(|since| since.as_secs()),
+                        last_played: last_played.get(&app.app_id).copied(),
+                        state_flags: app
+                            .state_flags
+                            .map(|flags| flags.flags().map(|flag| format!("{flag:?}")).collect())
+                            .unwrap_or_default(),
+                        owners: Vec::new(),
+                        disk_usage: None,
+                    });
+                }
+            }
+        }
+
+        // Nobody has logged in to a fresh installation, so it has no userdata
+        if !self.steam_dir.path().join("userdata").is_dir() {
+            return Ok(());
+        }
+        let accounts = find_accounts(self.steam_dir.path());
+        let owners = userdata::shortcut_owners(self.steam_dir.path());
+        let mut seen_shortcuts = HashSet::new();
+        for shortcut in self.steam_dir.shortcuts()? {
+            let shortcut = shortcut?;
+            // Accounts sharing a shortcut each list it; show it once with every owner
+            if !seen_shortcuts.insert(shortcut.app_id) {
+                continue;
+            }
+            let owner_ids = owners.get(&shortcut.app_id);
+            let prefix_path = find_prefix(&library_paths, shortcut.app_id);
+            if compat_tools.contains_key(&shortcut.app_id) || prefix_path.is_some() {
+                games.push(Game {
+                    name: shortcut.app_name,
+                    app_id: shortcut.app_id,
+                    kind: GameKind::Shortcut,
+                    installation: installation.clone(),
+                    install_path: shortcut.start_dir.trim_matches('"').into(),
+                    prefix_path,
+                    compat_tool: tool_name(&compat_tools, shortcut.app_id),
+                    compat_tool_path: tool_path(&tools, &compat_tools, shortcut.app_id),
+                    library: None,
+                    size_on_disk: None,
+                    shader_cache_path: find_shader_cache(&library_paths, shortcut.app_id),
+                    build_id: None,
+                    last_updated: None,
+                    last_played: last_played.get(&shortcut.app_id).copied(),
+                    state_flags: Vec::new(),
+                    owners: accounts
+                        .iter()
+                        .filter(|account| {
+                            owner_ids.is_some_and(|ids| ids.contains(&account.account_id))
+                        })
+                        .cloned()
+                        .collect(),
+                    disk_usage: None,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Scans the Steam installation found by [`SteamDir::locate`].
+pub fn scan() -> Result<Vec<Game>> {
+    Scanner::locate()?.scan()
+}
+
+/// Scans several Steam installations and merges their games.
+///
+/// A library folder shared by more than one installation is only scanned once,
+/// and its games are credited to the first installation that lists it.
+pub fn scan_all(scanners: &[Scanner]) -> Result<Vec<Game>> {
+    let mut games = Vec::new();
+    let mut seen_libraries = HashSet::new();
+    for scanner in scanners {
+        scanner.scan_into(&mut games, &mut seen_libraries)?;
+    }
+    Ok(games)
+}
+
+fn tool_name(compat_tools: &HashMap<u32, CompatTool>, app_id: u32) -> Option<String> {
+    compat_tools.get(&app_id).and_then(|tool| tool.name.clone())
+}
+
+/// Folder of the tool mapped to `app_id`, if it is installed.
+fn tool_path(
+    tools: &[Tool],
+    compat_tools: &HashMap<u32, CompatTool>,
+    app_id: u32,
+) -> Option<PathBuf> {
+    let name = tool_name(compat_tools, app_id)?;
+    resolve_tool(tools, &name).map(|tool| tool.path.clone())
+}
+
+/// Looks for `steamapps/compatdata/<app_id>/pfx` in every library folder.
+fn find_prefix(library_paths: &[PathBuf], app_id: u32) -> Option<PathBuf> {
+    find_app_dir(library_paths, "compatdata", app_id)
+        .map(|compatdata| compatdata.join("pfx"))
+        .filter(|pfx| pfx.is_dir())
+}
+
+/// Looks for `steamapps/shadercache/<app_id>` in every library folder.
+fn find_shader_cache(library_paths: &[PathBuf], app_id: u32) -> Option<PathBuf> {
+    find_app_dir(library_paths, "shadercache", app_id)
+}
+
+fn find_app_dir(library_paths: &[PathBuf], kind: &str, app_id: u32) -> Option<PathBuf> {
+    library_paths
+        .iter()
+        .map(|library| {
+            library
+                .join("steamapps")
+                .join(kind)
+                .join(app_id.to_string())
+        })
+        .find(|dir| dir.is_dir())
+}
@@ -0,0 +1,78 @@
+use std::cmp::Ordering;
+
+use crate::Game;
+
+/// Orders in which a list of games can be shown.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortKey {
+    /// Keep the order the games were found in, or search relevance.
+    #[default]
+    Default,
+    Name,
+    AppId,
+    /// Measured disk usage, see [`crate::disk_usage`].
+    Size,
+    LastPlayed,
+    LastUpdated,
+    Library,
+    /// Steam games before non-Steam shortcuts.
+    Kind,
+}
+
+impl SortKey {
+    pub const ALL: [SortKey; 8] = [
+        Self::Default,
+        Self::Name,
+        Self::AppId,
+        Self::Size,
+        Self::LastPlayed,
+        Self::LastUpdated,
+        Self::Library,
+        Self::Kind,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Name => "name",
+            Self::AppId => "app-id",
+            Self::Size => "size",
+            Self::LastPlayed => "last-played",
+            Self::LastUpdated => "last-updated",
+            Self::Library => "library",
+            Self::Kind => "kind",
+        }
+    }
+
+    /// Parses a name returned by [`SortKey::as_str`].
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|key| key.as_str() == name)
+    }
+
+    /// The key after this one, wrapping around to [`SortKey::Default`].
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|&key| key == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    /// Compares two games by this key, falling back to their names.
+    pub fn compare(self, a: &Game, b: &Game) -> Ordering {
+        let by_key = match self {
+            Self::Default | Self::Name => Ordering::Equal,
+            Self::AppId => a.app_id.cmp(&b.app_id),
+            Self::Size => size(a).cmp(&size(b)),
+            Self::LastPlayed => a.last_played.cmp(&b.last_played),
+            Self::LastUpdated => a.last_updated.cmp(&b.last_updated),
+            Self::Library => a.library.cmp(&b.library),
+            Self::Kind => a.is_non_steam().cmp(&b.is_non_steam()),
+        };
+        if self == Self::Default {
+            return by_key;
+        }
+        by_key.then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    }
+}
+
+fn size(game: &Game) -> u64 {
+    game.disk_usage.map_or(0, |usage| usage.total())
+}
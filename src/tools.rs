@@ -0,0 +1,325 @@
+//! Compatibility tools (Proton and Wine builds) installed for Steam.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use keyvalues_parser::Vdf;
+use serde::Serialize;
+use steamlocate::Library;
+
+use crate::{vdf, Error, FileEdit};
+
+/// Where custom tools are installed system-wide by distribution packages.
+const SYSTEM_TOOLS_DIR: &str = "/usr/share/steam/compatibilitytools.d";
+
+/// Mapping names of the Proton builds Valve ships as apps, by app ID. Steam
+/// takes these from its app info rather than from the installed folder.
+const VALVE_PROTON_NAMES: [(u32, &str); 14] = [
+    (858_280, "proton_37"),
+    (930_400, "proton_37_beta"),
+    (961_940, "proton_316"),
+    (996_510, "proton_316_beta"),
+    (1_054_830, "proton_42"),
+    (1_113_280, "proton_411"),
+    (1_245_040, "proton_5"),
+    (1_420_170, "proton_513"),
+    (1_580_130, "proton_63"),
+    (1_887_720, "proton_7"),
+    (2_348_590, "proton_8"),
+    (2_805_730, "proton_9"),
+    (1_493_710, "proton_experimental"),
+    (2_180_100, "proton_hotfix"),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolKind {
+    /// A Proton build shipped by Valve as an app in `steamapps/common`.
+    Official,
+    /// A build such as GE-Proton dropped into `compatibilitytools.d`.
+    Custom,
+}
+
+/// An installed compatibility tool.
+#[derive(Clone, Debug, Serialize)]
+pub struct Tool {
+    /// Name used in `CompatToolMapping`, e.g. `proton_9` or `GE-Proton9-20`.
+    pub name: String,
+    pub display_name: String,
+    pub path: PathBuf,
+    pub kind: ToolKind,
+    /// Contents of the tool's `version` file without the leading build
+    /// timestamp, e.g. `GE-Proton9-20` or `proton-9.0-2`.
+    pub version: Option<String>,
+    /// Whether Steam uses this tool for every game without its own mapping.
+    pub default: bool,
+}
+
+/// Finds the tools in the `compatibilitytools.d` folders of `steam_root` and
+/// the system, and the official Proton builds in every library.
+pub fn find_tools(steam_root: &Path, library_paths: &[PathBuf]) -> Vec<Tool> {
+    let mut tools = Vec::new();
+    for dir in [
+        steam_root.join("compatibilitytools.d"),
+        PathBuf::from(SYSTEM_TOOLS_DIR),
+    ] {
+        for tool_dir in sub_dirs(&dir) {
+            tools.extend(custom_tools(&tool_dir));
+        }
+    }
+
+    for library in library_paths {
+        // Manifests are only read for libraries holding a Proton build
+        let mut installed = None;
+        for app_dir in sub_dirs(&library.join("steamapps").join("common")) {
+            if !app_dir.join("proton").is_file() {
+                continue;
+            }
+            let display_name = file_name(&app_dir);
+            if tools.iter().any(|tool: &Tool| tool.path == app_dir) {
+                continue;
+            }
+            let apps = installed.get_or_insert_with(|| installed_apps(library));
+            let name = declared_tools(&app_dir)
+                .and_then(|declared| declared.into_iter().next())
+                .map(|tool| tool.name)
+                .or_else(|| {
+                    let app_id = apps.iter().find(|(_, dir)| *dir == display_name)?.0;
+                    valve_proton_name(app_id).map(str::to_string)
+                })
+                .unwrap_or_else(|| official_name(&display_name));
+            tools.push(Tool {
+                name,
+                display_name,
+                version: read_version(&app_dir),
+                path: app_dir,
+                kind: ToolKind::Official,
+                default: false,
+            });
+        }
+    }
+    tools
+}
+
+/// Finds the installed tool Steam uses for the mapping `name`.
+pub fn resolve_tool<'a>(tools: &'a [Tool], name: &str) -> Option<&'a Tool> {
+    tools.iter().find(|tool| tool.name == name)
+}
+
+/// Prepares mapping `app_id` to the tool named `tool_name` in the
+/// `CompatToolMapping` of `config/config.vdf`, as Steam's compatibility
+/// settings do. Nothing is written until the edit is applied.
+pub fn map_compat_tool(steam_root: &Path, app_id: u32, tool_name: &str) -> crate::Result<FileEdit> {
+    let path = steam_root.join("config").join("config.vdf");
+    let original = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(source) => return Err(Error::Io { path, source }),
+    };
+    let app_id = app_id.to_string();
+    let mapping = [
+        "InstallConfigStore",
+        "Software",
+        "Valve",
+        "Steam",
+        "CompatToolMapping",
+        &app_id,
+    ];
+    // 250 is the priority Steam gives mappings chosen by the user
+    let updated = vdf::set_text_value(&original, &mapping, "name", tool_name, true)
+        .and_then(|text| vdf::set_text_value(&text, &mapping, "config", "", false))
+        .and_then(|text| vdf::set_text_value(&text, &mapping, "priority", "250", false));
+    match updated {
+        Some(updated) => Ok(FileEdit {
+            path,
+            original,
+            updated,
+        }),
+        None => Err(Error::Parse { path }),
+    }
+}
+
+/// The tools declared by a folder's `compatibilitytool.vdf`, or the folder
+/// itself named after its directory when the file is missing or unreadable.
+fn custom_tools(tool_dir: &Path) -> Vec<Tool> {
+    declared_tools(tool_dir).unwrap_or_else(|| {
+        let name = file_name(tool_dir);
+        vec![Tool {
+            display_name: name.clone(),
+            name,
+            path: tool_dir.to_path_buf(),
+            kind: ToolKind::Custom,
+            version: read_version(tool_dir),
+            default: false,
+        }]
+    })
+}
+
+/// The tools declared by a folder's `compatibilitytool.vdf`, if it has a
+/// readable one declaring any.
+fn declared_tools(tool_dir: &Path) -> Option<Vec<Tool>> {
+    let text = fs::read_to_string(tool_dir.join("compatibilitytool.vdf")).ok()?;
+    let manifest = Vdf::parse(&text).ok()?;
+    let declared = manifest
+        .value
+        .get_obj()
+        .and_then(|obj| vdf::get(obj, "compat_tools"))
+        .and_then(|tools| tools.get_obj())?;
+
+    let tools: Vec<Tool> = declared
+        .iter()
+        .map(|(name, values)| {
+            let tool = values.first().and_then(|tool| tool.get_obj());
+            let field = |key| tool.and_then(|tool| vdf::get(tool, key)?.get_str());
+            let path = match field("install_path") {
+                Some(install_path) => tool_dir.join(install_path),
+                None => tool_dir.to_path_buf(),
+            };
+            Tool {
+                name: name.to_string(),
+                display_name: field("display_name").unwrap_or(name).to_string(),
+                path: fs::canonicalize(&path).unwrap_or(path),
+                kind: ToolKind::Custom,
+                version: read_version(tool_dir),
+                default: false,
+            }
+        })
+        .collect();
+    (!tools.is_empty()).then_some(tools)
+}
+
+/// App IDs and install folder names of the apps with a manifest in `library`.
+fn installed_apps(library: &Path) -> Vec<(u32, String)> {
+    let Ok(library) = Library::from_dir(library) else {
+        return Vec::new();
+    };
+    library
+        .apps()
+        .filter_map(Result::ok)
+        .map(|app| (app.app_id, app.install_dir))
+        .collect()
+}
+
+/// The mapping name Steam gives the official Proton build with `app_id`.
+fn valve_proton_name(app_id: u32) -> Option<&'static str> {
+    VALVE_PROTON_NAMES
+        .iter()
+        .find(|(id, _)| *id == app_id)
+        .map(|(_, name)| *name)
+}
+
+/// Reads `version`, which holds a build timestamp followed by the version.
+fn read_version(tool_dir: &Path) -> Option<String> {
+    let text = fs::read_to_string(tool_dir.join("version")).ok()?;
+    let text = text.trim();
+    let version = match text.split_once(char::is_whitespace) {
+        Some((stamp, version)) if stamp.chars().all(|c| c.is_ascii_digit()) => version.trim(),
+        _ => text,
+    };
+    (!version.is_empty()).then(|| version.to_string())
+}
+
+/// A guess at the mapping name of an official Proton folder for builds
+/// newer than [`VALVE_PROTON_NAMES`]: `Proton 10.0` is `proton_10` and
+/// `Proton 10.13` would be `proton_1013`.
+fn official_name(dir_name: &str) -> String {
+    let rest = dir_name
+        .strip_prefix("Proton")
+        .unwrap_or(dir_name)
+        .trim_start_matches([' ', '-']);
+    if let Some((major, minor)) = rest.split_once('.') {
+        if major
+            .chars()
+            .chain(minor.chars())
+            .all(|c| c.is_ascii_digit())
+        {
+            return if minor.trim_start_matches('0').is_empty() {
+                format!("proton_{major}")
+            } else {
+                format!("proton_{major}{minor}")
+            };
+        }
+    }
+    let words: Vec<String> = rest
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    format!("proton_{}", words.join("_"))
+}
+
+fn sub_dirs(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_valve_proton_builds() {
+        assert_eq!(valve_proton_name(2_805_730), Some("proton_9"));
+        assert_eq!(valve_proton_name(2_348_590), Some("proton_8"));
+        assert_eq!(valve_proton_name(1_887_720), Some("proton_7"));
+        assert_eq!(valve_proton_name(1_580_130), Some("proton_63"));
+        assert_eq!(valve_proton_name(1_420_170), Some("proton_513"));
+        assert_eq!(valve_proton_name(1_493_710), Some("proton_experimental"));
+        assert_eq!(valve_proton_name(2_180_100), Some("proton_hotfix"));
+        assert_eq!(valve_proton_name(374_320), None);
+    }
+
+    #[test]
+    fn reads_official_names_from_manifests() {
+        let root = std::env::temp_dir().join(format!("steam-locater-tools-{}", std::process::id()));
+        let steamapps = root.join("steamapps");
+        for dir in ["Proton 9.0 (Beta)", "Proton 10.0", "Custom Proton"] {
+            fs::create_dir_all(steamapps.join("common").join(dir)).unwrap();
+            fs::write(steamapps.join("common").join(dir).join("proton"), "").unwrap();
+        }
+        fs::write(
+            steamapps.join("appmanifest_2805730.acf"),
+            "\"AppState\"\n{\n\t\"appid\"\t\t\"2805730\"\n\t\"name\"\t\t\"Proton 9.0 (Beta)\"\n\t\"installdir\"\t\t\"Proton 9.0 (Beta)\"\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            steamapps
+                .join("common")
+                .join("Custom Proton")
+                .join("compatibilitytool.vdf"),
+            "\"compatibilitytools\"\n{\n\t\"compat_tools\"\n\t{\n\t\t\"custom_proton\"\n\t\t{\n\t\t\t\"install_path\"\t\".\"\n\t\t}\n\t}\n}\n",
+        )
+        .unwrap();
+
+        let tools = find_tools(&root.join("missing"), std::slice::from_ref(&root));
+        let names: Vec<(&str, &str)> = tools
+            .iter()
+            .filter(|tool| tool.kind == ToolKind::Official)
+            .map(|tool| (tool.display_name.as_str(), tool.name.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("Custom Proton", "custom_proton"),
+                ("Proton 10.0", "proton_10"),
+                ("Proton 9.0 (Beta)", "proton_9"),
+            ]
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,2841 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::stdout;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+use crossterm::{
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, TableState, Wrap},
+    Terminal,
+};
+use steam_locater::{
+    archive_orphan, back_up_prefix, back_up_saves, delete_orphan, dir_size, disk_usage,
+    find_accounts, find_backups, find_launch_options, find_save_backups, find_save_locations,
+    find_userdata, has_preset, launch_option_accounts, map_compat_tool, match_game, reset_prefix,
+    restore_backup, restore_saves, set_launch_options, steam_running, toggle_preset, Account,
+    Backup, DiskUsage, FileEdit, Folder, Game, Installation, LaunchOptions, LaunchOptionsEdit,
+    Orphan, OrphanKind, SaveBackup, SaveLocation, SortKey, Tool, ToolKind, UserdataFolder,
+    LAUNCH_PRESETS,
+};
+
+use crate::cli::OrphanAction;
+use crate::clipboard;
+use crate::config::{Config, Keys, Theme};
+use crate::opener::Opener;
+use crate::output::{format_size, format_timestamp};
+
+/// What the body of the screen lists.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum View {
+    Games,
+    Tools,
+    Orphans,
+    /// Backups of the selected game's prefix.
+    Backups,
+    /// Folders in the selected game's prefix that likely hold saves.
+    Saves,
+}
+
+/// An orphaned prefix or shader cache in the orphans view.
+struct OrphanEntry {
+    orphan: Orphan,
+    /// `None` until measured.
+    size: Option<u64>,
+    /// Marked to be deleted or archived together.
+    marked: bool,
+}
+
+/// Changing the selected game's compatibility tool: first a tool is picked,
+/// then the edit of `config.vdf` is shown for confirmation.
+enum ToolChange {
+    Picking {
+        tools: Vec<Tool>,
+        state: TableState,
+    },
+    Confirming {
+        tool: Tool,
+        edit: FileEdit,
+        steam_running: bool,
+    },
+}
+
+/// Editing the selected game's launch options: first the text is edited for
+/// one of the accounts, then the change is shown for confirmation.
+enum LaunchOptionsChange {
+    Editing {
+        /// The accounts that can have launch options for the game.
+        accounts: Vec<Account>,
+        /// Index into `accounts` of the one being edited.
+        account: usize,
+        text: String,
+    },
+    Confirming {
+        edit: LaunchOptionsEdit,
+        steam_running: bool,
+    },
+}
+
+/// Clearing the selected game's shader cache, waiting for confirmation.
+struct ShaderCacheClear {
+    path: PathBuf,
+    size: u64,
+    steam_running: bool,
+}
+
+/// A backup listed in the backups view.
+#[derive(Clone)]
+enum GameBackup {
+    Prefix(Backup),
+    Saves(SaveBackup),
+}
+
+impl GameBackup {
+    fn created(&self) -> u64 {
+        match self {
+            Self::Prefix(backup) => backup.created,
+            Self::Saves(backup) => backup.created,
+        }
+    }
+
+    /// The tarball's size, or the measured size of a save generation; `None`
+    /// while that is still being measured.
+    fn size(&self, save_sizes: &HashMap<PathBuf, u64>) -> Option<u64> {
+        match self {
+            Self::Prefix(backup) => Some(fs::metadata(&backup.archive).map_or(0, |m| m.len())),
+            Self::Saves(backup) => save_sizes.get(&backup.dir).copied(),
+        }
+    }
+}
+
+/// Restoring a backup of the selected game, waiting for confirmation.
+struct Restore {
+    backup: GameBackup,
+    steam_running: bool,
+}
+
+/// Resetting the selected game's prefix, waiting for confirmation.
+struct ResetRequest {
+    compat_data: PathBuf,
+    size: u64,
+    /// Copy `drive_c/users/steamuser` back into the fresh prefix.
+    keep_saves: bool,
+    steam_running: bool,
+}
+
+struct App {
+    view: View,
+    tool_change: Option<ToolChange>,
+    launch_options_change: Option<LaunchOptionsChange>,
+    /// Launch options of the game at this index into `items`, read when it is
+    /// selected because `localconfig.vdf` can be large.
+    launch_options: Option<(usize, Vec<LaunchOptions>)>,
+    items: Vec<Game>,
+    /// Indices into `items` of the games shown, in display order.
+    filtered_items: Vec<usize>,
+    search_query: String,
+    in_search_mode: bool,
+    /// Steam installations the games were scanned from.
+    installations: Vec<Installation>,
+    /// Index into `installations` of the one shown, or `None` for all of them.
+    installation_filter: Option<usize>,
+    /// Steam accounts of each installation, by its root.
+    accounts: BTreeMap<PathBuf, Vec<Account>>,
+    /// Compatibility tools of each installation, by its root. A game can only
+    /// be mapped to a tool of its own installation.
+    installation_tools: BTreeMap<PathBuf, Vec<Tool>>,
+    /// The account whose shortcuts are shown, or `None` for every account's.
+    account_filter: Option<u32>,
+    sort_key: SortKey,
+    sort_descending: bool,
+    status_message: String,
+    state: TableState,
+    sizes: Receiver<(usize, DiskUsage)>,
+    sizes_pending: usize,
+    /// Installed compatibility tools, shown in the tools view.
+    tools: Vec<Tool>,
+    /// Measured size of each of `tools`; `None` until measured.
+    tool_sizes: Vec<Option<u64>>,
+    tool_size_receiver: Receiver<(PathBuf, u64)>,
+    tools_state: TableState,
+    orphans: Vec<OrphanEntry>,
+    orphan_size_receiver: Receiver<(PathBuf, u64)>,
+    orphans_state: TableState,
+    /// Cleanup of the marked (or selected) orphans waiting for confirmation.
+    orphan_action: Option<OrphanAction>,
+    shader_cache_clear: Option<ShaderCacheClear>,
+    archive_dir: Option<PathBuf>,
+    /// Prefix backups of every game, newest first.
+    backups: Vec<Backup>,
+    backups_state: TableState,
+    restore: Option<Restore>,
+    backup_dir: Option<PathBuf>,
+    /// Save backups of every game, newest first.
+    save_backups: Vec<SaveBackup>,
+    /// Measured sizes of the save backup folders.
+    save_backup_sizes: HashMap<PathBuf, u64>,
+    save_backup_size_receiver: Receiver<(PathBuf, u64)>,
+    save_backup_dir: Option<PathBuf>,
+    save_generations: usize,
+    reset: Option<ResetRequest>,
+    /// Save folders of the selected game, found when the saves view opens.
+    saves: Vec<SaveLocation>,
+    saves_state: TableState,
+    trash_dir: Option<PathBuf>,
+    opener: Opener,
+    /// Folders being opened, with how the status line names them.
+    openings: Vec<(Receiver<Result<String, String>>, String)>,
+    theme: Theme,
+    keys: Keys,
+}
+
+impl App {
+    fn new(
+        items: Vec<Game>,
+        tools: Vec<Tool>,
+        installation_tools: BTreeMap<PathBuf, Vec<Tool>>,
+        orphans: Vec<Orphan>,
+        installations: Vec<Installation>,
+        opener: Opener,
+        config: &Config,
+    ) -> Self {
+        let filtered_items = (0..items.len()).collect();
+        let sizes_pending = items.len();
+        let sizes = measure_in_background(&items);
+        let tool_sizes = vec![None; tools.len()];
+        let tool_size_receiver =
+            measure_dirs_in_background(tools.iter().map(|tool| tool.path.clone()).collect());
+        let mut tools_state = TableState::default();
+        tools_state.select((!tools.is_empty()).then_some(0));
+        let orphan_size_receiver =
+            measure_dirs_in_background(orphans.iter().map(|orphan| orphan.path.clone()).collect());
+        let mut orphans_state = TableState::default();
+        orphans_state.select((!orphans.is_empty()).then_some(0));
+        let accounts = installations
+            .iter()
+            .map(|installation| (installation.path.clone(), find_accounts(&installation.path)))
+            .collect();
+        let save_backups: Vec<SaveBackup> = config
+            .save_backup_dir()
+            .map(|dir| find_save_backups(&dir))
+            .unwrap_or_default();
+        let save_backup_size_receiver = measure_dirs_in_background(
+            save_backups
+                .iter()
+                .map(|backup| backup.dir.clone())
+                .collect(),
+        );
+        let orphans = orphans
+            .into_iter()
+            .map(|orphan| OrphanEntry {
+                orphan,
+                size: None,
+                marked: false,
+            })
+            .collect();
+        Self {
+            view: View::Games,
+            tool_change: None,
+            launch_options_change: None,
+            launch_options: None,
+            items,
+            filtered_items,
+            search_query: String::new(),
+            in_search_mode: false,
+            installations,
+            installation_filter: None,
+            accounts,
+            installation_tools,
+            account_filter: None,
+            sort_key: config.default_sort,
+            sort_descending: config.sort_descending,
+            status_message: format!(
+                "Use '{}' to search, '{}' to open prefix, '{}'/'{}' to change sorting, '{}' to exit.",
+                config.keys.search,
+                config.keys.open_prefix,
+                config.keys.sort,
+                config.keys.reverse_sort,
+                config.keys.quit
+            ),
+            state: TableState::default(),
+            sizes,
+            sizes_pending,
+            tools,
+            tool_sizes,
+            tool_size_receiver,
+            tools_state,
+            orphans,
+            orphan_size_receiver,
+            orphans_state,
+            orphan_action: None,
+            shader_cache_clear: None,
+            archive_dir: config.archive_dir(),
+            backups: config
+                .backup_dir()
+                .map(|dir| find_backups(&dir))
+                .unwrap_or_default(),
+            backups_state: TableState::default(),
+            restore: None,
+            backup_dir: config.backup_dir(),
+            save_backups,
+            save_backup_sizes: HashMap::new(),
+            save_backup_size_receiver,
+            save_backup_dir: config.save_backup_dir(),
+            save_generations: config.save_generations,
+            reset: None,
+            saves: Vec::new(),
+            saves_state: TableState::default(),
+            trash_dir: config.trash_dir(),
+            opener,
+            openings: Vec::new(),
+            theme: config.theme,
+            keys: config.keys,
+        }
+    }
+
+    fn update_filter(&mut self) {
+        self.rebuild();
+        // The best match moves to the top, so select it
+        self.state.select(if self.filtered_items.is_empty() {
+            None
+        } else {
+            Some(0)
+        });
+    }
+
+    /// Recomputes `filtered_items` from the search query and sort order.
+    fn rebuild(&mut self) {
+        let mut ranked: Vec<(i64, usize)> = self
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, game)| {
+                self.installation_filter
+                    .is_none_or(|i| game.installation == self.installations[i].path)
+            })
+            .filter(|(_, game)| self.account_filter.is_none_or(|id| game.visible_to(id)))
+            .filter_map(|(i, game)| match_game(&self.search_query, game).map(|m| (m.score, i)))
+            .collect();
+        // Stable sort keeps discovery order among equally good matches
+        ranked.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+        self.filtered_items = ranked.into_iter().map(|(_, i)| i).collect();
+        let items = &self.items;
+        let key = self.sort_key;
+        self.filtered_items
+            .sort_by(|&a, &b| key.compare(&items[a], &items[b]));
+        if self.sort_descending {
+            self.filtered_items.reverse();
+        }
+    }
+
+    /// Rebuilds the list while keeping the selected game selected.
+    fn rebuild_keeping_selection(&mut self) {
+        let selected = self
+            .state
+            .selected()
+            .and_then(|i| self.filtered_items.get(i).copied());
+        self.rebuild();
+        if let Some(selected) = selected {
+            self.state
+                .select(self.filtered_items.iter().position(|&i| i == selected));
+        }
+    }
+
+    fn cycle_sort_key(&mut self) {
+        self.sort_key = self.sort_key.next();
+        self.rebuild_keeping_selection();
+    }
+
+    fn toggle_sort_direction(&mut self) {
+        self.sort_descending = !self.sort_descending;
+        self.rebuild_keeping_selection();
+    }
+
+    /// Shows only the next installation's games, then all of them again.
+    fn cycle_installation_filter(&mut self) {
+        self.installation_filter = match self.installation_filter {
+            None if !self.installations.is_empty() => Some(0),
+            Some(i) if i + 1 < self.installations.len() => Some(i + 1),
+            _ => None,
+        };
+        self.update_filter();
+    }
+
+    /// Every account of the scanned installations, once each.
+    fn known_accounts(&self) -> Vec<&Account> {
+        let mut accounts: Vec<&Account> = Vec::new();
+        for account in self.accounts.values().flatten() {
+            if !accounts
+                .iter()
+                .any(|known| known.account_id == account.account_id)
+            {
+                accounts.push(account);
+            }
+        }
+        accounts
+    }
+
+    /// Shows only the next account's shortcuts, then every account's again.
+    fn cycle_account_filter(&mut self) {
+        let accounts: Vec<u32> = self
+            .known_accounts()
+            .iter()
+            .map(|account| account.account_id)
+            .collect();
+        self.account_filter = match self.account_filter {
+            None => accounts.first().copied(),
+            Some(id) => accounts
+                .iter()
+                .position(|&known| known == id)
+                .and_then(|i| accounts.get(i + 1).copied()),
+        };
+        self.update_filter();
+    }
+
+    /// Label of the account filter, e.g. a persona name.
+    fn account_label(&self) -> String {
+        match self.account_filter {
+            None => "all".to_string(),
+            Some(id) => self
+                .known_accounts()
+                .into_iter()
+                .find(|account| account.account_id == id)
+                .map_or_else(|| id.to_string(), Account::display_name),
+        }
+    }
+
+    /// Label of the installation filter, e.g. `Flatpak`.
+    fn installation_label(&self) -> String {
+        match self.installation_filter {
+            None => "all".to_string(),
+            Some(i) => installation_label(&self.installations, i),
+        }
+    }
+
+    /// Stores sizes measured by the background thread since the last call.
+    fn receive_sizes(&mut self) {
+        let mut received = false;
+        for (i, usage) in self.sizes.try_iter() {
+            self.items[i].disk_usage = Some(usage);
+            self.sizes_pending -= 1;
+            received = true;
+        }
+        if received && self.sort_key == SortKey::Size {
+            self.rebuild_keeping_selection();
+        }
+        for (path, size) in self.tool_size_receiver.try_iter() {
+            if let Some(i) = self.tools.iter().position(|tool| tool.path == path) {
+                self.tool_sizes[i] = Some(size);
+            }
+        }
+        self.save_backup_sizes
+            .extend(self.save_backup_size_receiver.try_iter());
+        for (path, size) in self.orphan_size_receiver.try_iter() {
+            if let Some(entry) = self.orphans.iter_mut().find(|e| e.orphan.path == path) {
+                entry.size = Some(size);
+            }
+        }
+    }
+
+    /// Shows `view`, or the games again when it is already shown.
+    fn toggle_view(&mut self, view: View) {
+        self.view = if self.view == view { View::Games } else { view };
+        if self.view == View::Backups {
+            let count = self.game_backups().len();
+            self.backups_state.select((count > 0).then_some(0));
+        }
+        if self.view == View::Saves {
+            self.saves = self
+                .selected_game()
+                .map(find_save_locations)
+                .unwrap_or_default();
+            self.saves_state
+                .select((!self.saves.is_empty()).then_some(0));
+        }
+    }
+
+    fn move_save_selection(&mut self, step: isize) {
+        let len = self.saves.len() as isize;
+        if let Some(i) = self.saves_state.selected() {
+            self.saves_state
+                .select(Some((i as isize + step).rem_euclid(len) as usize));
+        }
+    }
+
+    fn selected_save(&self) -> Option<&SaveLocation> {
+        self.saves_state.selected().and_then(|i| self.saves.get(i))
+    }
+
+    fn open_selected_save(&mut self) {
+        if let Some(location) = self.selected_save() {
+            let path = location.path.clone();
+            self.open_path(&path, path.display().to_string());
+        }
+    }
+
+    fn copy_selected_save_path(&mut self) {
+        if let Some(location) = self.selected_save() {
+            let path = location.path.display().to_string();
+            self.status_message = match clipboard::copy(&path) {
+                Ok(program) => format!("Copied {path} with {program}."),
+                Err(message) => message,
+            };
+        }
+    }
+
+    /// Prefix and save backups of the selected game, newest first.
+    fn game_backups(&self) -> Vec<GameBackup> {
+        let Some(game) = self.selected_game() else {
+            return Vec::new();
+        };
+        let prefixes = self
+            .backups
+            .iter()
+            .filter(|backup| backup.app_id == game.app_id)
+            .cloned()
+            .map(GameBackup::Prefix);
+        let saves = self
+            .save_backups
+            .iter()
+            .filter(|backup| backup.app_id == game.app_id)
+            .cloned()
+            .map(GameBackup::Saves);
+        let mut backups: Vec<GameBackup> = prefixes.chain(saves).collect();
+        backups.sort_by_key(|backup| std::cmp::Reverse(backup.created()));
+        backups
+    }
+
+    fn move_backup_selection(&mut self, step: isize) {
+        let len = self.game_backups().len() as isize;
+        if let Some(i) = self.backups_state.selected() {
+            self.backups_state
+                .select(Some((i as isize + step).rem_euclid(len) as usize));
+        }
+    }
+
+    fn selected_backup(&self) -> Option<GameBackup> {
+        self.backups_state
+            .selected()
+            .and_then(|i| self.game_backups().into_iter().nth(i))
+    }
+
+    /// Backs up the selected game's prefix into the backup folder.
+    fn back_up_selected(&mut self) {
+        let Some(game) = self.selected_game() else {
+            return;
+        };
+        let Some(dir) = &self.backup_dir else {
+            self.status_message = "Set backup_dir in the config to back up prefixes.".to_string();
+            return;
+        };
+        match back_up_prefix(game, dir) {
+            Ok(backup) => {
+                self.status_message = format!(
+                    "Backed up the prefix of {} to {}.",
+                    game.name,
+                    backup.archive.display()
+                );
+                self.backups.insert(0, backup);
+            }
+            Err(error) => self.status_message = format!("Could not back up: {error}"),
+        }
+    }
+
+    /// Copies the selected game's saves into a new generation, dropping the
+    /// oldest ones beyond the configured number.
+    fn back_up_selected_saves(&mut self) {
+        let Some(game) = self.selected_game() else {
+            return;
+        };
+        let Some(dir) = &self.save_backup_dir else {
+            self.status_message = "Set save_backup_dir in the config to back up saves.".to_string();
+            return;
+        };
+        self.status_message = match back_up_saves(game, dir, self.save_generations) {
+            Ok(Some(backup)) => format!(
+                "Backed up {} save folders of {} to {}.",
+                backup.folders.len(),
+                game.name,
+                backup.dir.display()
+            ),
+            Ok(None) => format!("{} has no saves.", game.name),
+            Err(error) => format!("Could not back up the saves: {error}"),
+        };
+        self.save_backups = find_save_backups(dir);
+        let unmeasured = self
+            .save_backups
+            .iter()
+            .filter(|backup| !self.save_backup_sizes.contains_key(&backup.dir))
+            .map(|backup| backup.dir.clone())
+            .collect();
+        self.save_backup_size_receiver = measure_dirs_in_background(unmeasured);
+    }
+
+    /// Asks to confirm restoring the selected backup.
+    fn request_restore(&mut self) {
+        if let Some(backup) = self.selected_backup() {
+            self.restore = Some(Restore {
+                backup,
+                steam_running: steam_running(),
+            });
+        }
+    }
+
+    /// Restores the confirmed backup over the selected game's prefix or saves.
+    fn confirm_restore(&mut self) {
+        let Some(Restore { backup, .. }) = self.restore.take() else {
+            return;
+        };
+        let Some(i) = self
+            .state
+            .selected()
+            .and_then(|i| self.filtered_items.get(i).copied())
+        else {
+            return;
+        };
+        let game = &mut self.items[i];
+        let restored = match &backup {
+            GameBackup::Prefix(backup) => restore_backup(backup, game).map(|pfx| {
+                game.prefix_path = Some(pfx);
+                "prefix"
+            }),
+            GameBackup::Saves(backup) => restore_saves(backup).map(|()| "saves"),
+        };
+        self.status_message = match restored {
+            Ok(what) => format!(
+                "Restored the {what} of {} from {}.",
+                game.name,
+                format_timestamp(backup.created())
+            ),
+            Err(error) => format!("Could not restore: {error}"),
+        };
+    }
+
+    /// Asks to confirm resetting the selected game's prefix.
+    fn request_reset(&mut self) {
+        let Some(game) = self.selected_game() else {
+            return;
+        };
+        let Some(compat_data) = game.folder(Folder::CompatData) else {
+            self.status_message = format!("{} has no prefix.", game.name);
+            return;
+        };
+        if self.trash_dir.is_none() {
+            self.status_message = "Set trash_dir in the config to reset prefixes.".to_string();
+            return;
+        }
+        let size = match game.disk_usage {
+            Some(usage) => usage.compat_data,
+            None => dir_size(&compat_data),
+        };
+        self.reset = Some(ResetRequest {
+            compat_data,
+            size,
+            keep_saves: true,
+            steam_running: steam_running(),
+        });
+    }
+
+    /// Moves the selected game's prefix to the trash.
+    fn confirm_reset(&mut self) {
+        let (Some(request), Some(trash_dir)) = (self.reset.take(), &self.trash_dir) else {
+            return;
+        };
+        let Some(i) = self
+            .state
+            .selected()
+            .and_then(|i| self.filtered_items.get(i).copied())
+        else {
+            return;
+        };
+        let game = &mut self.items[i];
+        self.status_message = match reset_prefix(game, trash_dir, request.keep_saves) {
+            Ok(reset) => {
+                if reset.kept.is_none() {
+                    game.prefix_path = None;
+                }
+                if let Some(usage) = &mut game.disk_usage {
+                    usage.compat_data = dir_size(&reset.compat_data);
+                }
+                format!(
+                    "Moved {} ({}) to {}{}.",
+                    reset.compat_data.display(),
+                    format_size(reset.size),
+                    reset.trash.display(),
+                    if reset.kept.is_some() {
+                        "; kept drive_c/users/steamuser"
+                    } else {
+                        ""
+                    }
+                )
+            }
+            Err(error) => format!("Could not reset the prefix: {error}"),
+        };
+    }
+
+    /// Total size of the selected game's backups.
+    fn backup_totals(&self) -> Vec<String> {
+        let backups = self.game_backups();
+        let sizes: Vec<Option<u64>> = backups
+            .iter()
+            .map(|backup| backup.size(&self.save_backup_sizes))
+            .collect();
+        let total: u64 = sizes.iter().flatten().sum();
+        let mut totals = vec![format!("{} backups: {}", backups.len(), format_size(total))];
+        let pending = sizes.iter().filter(|size| size.is_none()).count();
+        if pending > 0 {
+            totals.push(format!("measuring {pending} more…"));
+        }
+        totals
+    }
+
+    fn next_tool(&mut self) {
+        let len = self.tools.len();
+        if let Some(i) = self.tools_state.selected() {
+            self.tools_state.select(Some((i + 1) % len));
+        }
+    }
+
+    fn previous_tool(&mut self) {
+        let len = self.tools.len();
+        if let Some(i) = self.tools_state.selected() {
+            self.tools_state.select(Some((i + len - 1) % len));
+        }
+    }
+
+    fn selected_tool(&self) -> Option<&Tool> {
+        self.tools_state.selected().and_then(|i| self.tools.get(i))
+    }
+
+    /// Games mapped to `tool` in any installation.
+    fn tool_games(&self, tool: &Tool) -> Vec<&Game> {
+        self.items
+            .iter()
+            .filter(|game| game.compat_tool_path.as_ref() == Some(&tool.path))
+            .collect()
+    }
+
+    /// Whether no game uses `tool`, so it could be removed.
+    fn tool_unused(&self, tool: &Tool) -> bool {
+        !tool.default && self.tool_games(tool).is_empty()
+    }
+
+    fn move_orphan_selection(&mut self, step: isize) {
+        let len = self.orphans.len() as isize;
+        if let Some(i) = self.orphans_state.selected() {
+            self.orphans_state
+                .select(Some((i as isize + step).rem_euclid(len) as usize));
+        }
+    }
+
+    fn toggle_orphan_mark(&mut self) {
+        if let Some(i) = self.orphans_state.selected() {
+            self.orphans[i].marked = !self.orphans[i].marked;
+            self.move_orphan_selection(1);
+        }
+    }
+
+    /// Indices of the marked orphans, or of the selected one when none are marked.
+    fn orphan_targets(&self) -> Vec<usize> {
+        let marked: Vec<usize> = (0..self.orphans.len())
+            .filter(|&i| self.orphans[i].marked)
+            .collect();
+        if marked.is_empty() {
+            self.orphans_state.selected().into_iter().collect()
+        } else {
+            marked
+        }
+    }
+
+    /// Asks to confirm `action` on the orphan targets.
+    fn request_orphan_action(&mut self, action: OrphanAction) {
+        if self.orphan_targets().is_empty() {
+            return;
+        }
+        if action == OrphanAction::Archive && self.archive_dir.is_none() {
+            self.status_message = "Set archive_dir in the config to archive folders.".to_string();
+            return;
+        }
+        self.orphan_action = Some(action);
+    }
+
+    /// Deletes or archives the orphan targets and drops them from the list.
+    fn confirm_orphan_action(&mut self) {
+        let Some(action) = self.orphan_action.take() else {
+            return;
+        };
+        let targets = self.orphan_targets();
+        let mut done = 0;
+        let mut failure = None;
+        for &i in &targets {
+            let orphan = &self.orphans[i].orphan;
+            let result = match (action, &self.archive_dir) {
+                (OrphanAction::Archive, Some(dir)) => archive_orphan(orphan, dir).map(drop),
+                _ => delete_orphan(orphan),
+            };
+            match result {
+                Ok(()) => done += 1,
+                Err(error) => failure = Some(format!("{}: {error}", orphan.path.display())),
+            }
+        }
+        // Keep the ones that failed, which still exist
+        self.orphans.retain(|entry| entry.orphan.path.exists());
+        self.orphans_state
+            .select((!self.orphans.is_empty()).then(|| {
+                let selected = self.orphans_state.selected().unwrap_or(0);
+                selected.min(self.orphans.len() - 1)
+            }));
+
+        let verb = match action {
+            OrphanAction::Delete => "Deleted",
+            OrphanAction::Archive => "Archived",
+        };
+        self.status_message = match failure {
+            None => format!("{verb} {done} orphaned folders."),
+            Some(error) => format!(
+                "{verb} {done} of {} orphaned folders; {error}",
+                targets.len()
+            ),
+        };
+    }
+
+    /// Total size of all orphans and of the marked ones.
+    fn orphan_totals(&self) -> Vec<String> {
+        let total: u64 = self.orphans.iter().filter_map(|entry| entry.size).sum();
+        let marked: u64 = self
+            .orphans
+            .iter()
+            .filter(|entry| entry.marked)
+            .filter_map(|entry| entry.size)
+            .sum();
+        let mut totals = vec![
+            format!(
+                "{} orphaned folders: {}",
+                self.orphans.len(),
+                format_size(total)
+            ),
+            format!("marked: {}", format_size(marked)),
+        ];
+        let pending = self
+            .orphans
+            .iter()
+            .filter(|entry| entry.size.is_none())
+            .count();
+        if pending > 0 {
+            totals.push(format!("measuring {pending} more…"));
+        }
+        totals
+    }
+
+    /// Total size of all tools and of the unused ones.
+    fn tool_totals(&self) -> Vec<String> {
+        let mut total = 0;
+        let mut unused = 0;
+        for (tool, size) in self.tools.iter().zip(&self.tool_sizes) {
+            let size = size.unwrap_or(0);
+            total += size;
+            if self.tool_unused(tool) {
+                unused += size;
+            }
+        }
+        let mut totals = vec![
+            format!("{} tools: {}", self.tools.len(), format_size(total)),
+            format!("unused: {}", format_size(unused)),
+        ];
+        let pending = self.tool_sizes.iter().filter(|size| size.is_none()).count();
+        if pending > 0 {
+            totals.push(format!("measuring {pending} more tools…"));
+        }
+        totals
+    }
+
+    fn open_selected_tool(&mut self) {
+        if let Some(tool) = self.selected_tool() {
+            let (path, name) = (tool.path.clone(), tool.display_name.clone());
+            self.open_path(&path, name);
+        }
+    }
+
+    fn enter_search_mode(&mut self) {
+        self.in_search_mode = true;
+    }
+
+    fn exit_search_mode(&mut self) {
+        self.in_search_mode = false;
+        self.search_query.clear();
+        self.update_filter();
+    }
+
+    fn next(&mut self) {
+        let len = self.filtered_items.len();
+        if len == 0 {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) => {
+                if i >= len - 1 {
+                    0
+                } else {
+                    i + 1
+                }
+            }
+            None => 0,
+        };
+        self.state.select(Some(i));
+    }
+
+    fn previous(&mut self) {
+        let len = self.filtered_items.len();
+        if len == 0 {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) => {
+                if i == 0 {
+                    len - 1
+                } else {
+                    i - 1
+                }
+            }
+            None => 0,
+        };
+        self.state.select(Some(i));
+    }
+
+    fn selected_game(&self) -> Option<&Game> {
+        self.state
+            .selected()
+            .and_then(|i| self.filtered_items.get(i))
+            .map(|&i| &self.items[i])
+    }
+
+    fn open_selected(&mut self, folder: Folder) {
+        let Some(game) = self.selected_game() else {
+            return;
+        };
+        match game.folder(folder) {
+            Some(path) if path.exists() => self.open_path(&path, folder.describe().to_string()),
+            Some(_) => self.status_message = "Folder does not exist.".to_string(),
+            None => self.status_message = format!("No {} for this game.", folder.describe()),
+        }
+    }
+
+    /// Starts opening `path`; the outcome reaches the status line once the
+    /// opener has had time to fail.
+    fn open_path(&mut self, path: &Path, name: String) {
+        self.status_message = format!("Opening {name}…");
+        self.openings.push((self.opener.open(path), name));
+    }
+
+    /// Reports the openings that finished since the last call.
+    fn receive_openings(&mut self) {
+        let status_message = &mut self.status_message;
+        self.openings
+            .retain(|(receiver, name)| match receiver.try_recv() {
+                Ok(Ok(program)) => {
+                    *status_message = format!("Opened {name} with {program}.");
+                    false
+                }
+                Ok(Err(message)) => {
+                    *status_message = message;
+                    false
+                }
+                Err(TryRecvError::Empty) => true,
+                Err(TryRecvError::Disconnected) => false,
+            });
+    }
+
+    /// The userdata folders every account of its installation keeps for `game`.
+    fn game_userdata(&self, game: &Game) -> Vec<UserdataFolder> {
+        let accounts = self
+            .accounts
+            .get(&game.installation)
+            .map_or(&[][..], Vec::as_slice);
+        find_userdata(game, accounts)
+    }
+
+    /// Opens the userdata folder numbered `n` in the details pane, from 0.
+    fn open_userdata(&mut self, n: usize) {
+        let Some(game) = self.selected_game() else {
+            return;
+        };
+        match self.game_userdata(game).get(n) {
+            Some(folder) => {
+                let path = folder.path.clone();
+                self.open_path(&path, path.display().to_string());
+            }
+            None => {
+                self.status_message = format!("{} has no userdata folder {}.", game.name, n + 1)
+            }
+        }
+    }
+
+    /// Reads the selected game's launch options unless they are cached.
+    fn load_launch_options(&mut self) {
+        let Some(i) = self
+            .state
+            .selected()
+            .and_then(|i| self.filtered_items.get(i).copied())
+        else {
+            return;
+        };
+        if self
+            .launch_options
+            .as_ref()
+            .is_some_and(|(cached, _)| *cached == i)
+        {
+            return;
+        }
+        let game = &self.items[i];
+        let accounts = self
+            .accounts
+            .get(&game.installation)
+            .map_or(&[][..], Vec::as_slice);
+        self.launch_options = Some((i, find_launch_options(game, accounts)));
+    }
+
+    /// The cached launch options of the selected game.
+    fn selected_launch_options(&self) -> &[LaunchOptions] {
+        match (&self.launch_options, self.state.selected()) {
+            (Some((cached, options)), Some(i)) if self.filtered_items.get(i) == Some(cached) => {
+                options
+            }
+            _ => &[],
+        }
+    }
+
+    /// Opens the launch options editor for the selected game, starting with
+    /// the filtered account, the last one to sign in or the first one.
+    fn start_launch_options_edit(&mut self) {
+        let Some(game) = self.selected_game() else {
+            return;
+        };
+        let known = self
+            .accounts
+            .get(&game.installation)
+            .map_or(&[][..], Vec::as_slice);
+        let accounts = launch_option_accounts(game, known);
+        if accounts.is_empty() {
+            self.status_message = format!("No account has {}.", game.name);
+            return;
+        }
+        let account = accounts
+            .iter()
+            .position(|account| Some(account.account_id) == self.account_filter)
+            .or_else(|| accounts.iter().position(|account| account.most_recent))
+            .unwrap_or(0);
+        let text = self.current_launch_options(&accounts[account]);
+        self.launch_options_change = Some(LaunchOptionsChange::Editing {
+            accounts,
+            account,
+            text,
+        });
+    }
+
+    /// The launch options `account` set for the selected game.
+    fn current_launch_options(&self, account: &Account) -> String {
+        self.selected_launch_options()
+            .iter()
+            .find(|options| options.account.account_id == account.account_id)
+            .map(|options| options.options.clone())
+            .unwrap_or_default()
+    }
+
+    /// Switches the editor to the next account, with its own options.
+    fn next_launch_options_account(&mut self) {
+        let Some(LaunchOptionsChange::Editing {
+            accounts, account, ..
+        }) = &self.launch_options_change
+        else {
+            return;
+        };
+        let next = (account + 1) % accounts.len();
+        let text = self.current_launch_options(&accounts[next]);
+        if let Some(LaunchOptionsChange::Editing {
+            account, text: old, ..
+        }) = &mut self.launch_options_change
+        {
+            *account = next;
+            *old = text;
+        }
+    }
+
+    /// Prepares the edit of the typed options and asks for confirmation.
+    fn review_launch_options(&mut self) {
+        let Some(LaunchOptionsChange::Editing {
+            accounts,
+            account,
+            text,
+        }) = &self.launch_options_change
+        else {
+            return;
+        };
+        let Some(game) = self.selected_game() else {
+            return;
+        };
+        let account = &accounts[*account];
+        match set_launch_options(game, account, text.trim()) {
+            Ok(edit) if edit.is_unchanged() => {
+                self.status_message = format!(
+                    "The launch options of {} for {} are unchanged.",
+                    game.name,
+                    account.display_name()
+                );
+                self.launch_options_change = None;
+            }
+            Ok(edit) => {
+                self.launch_options_change = Some(LaunchOptionsChange::Confirming {
+                    edit,
+                    steam_running: steam_running(),
+                });
+            }
+            Err(error) => {
+                self.status_message = format!("Could not change the launch options: {error}");
+                self.launch_options_change = None;
+            }
+        }
+    }
+
+    /// Writes the confirmed launch options.
+    fn confirm_launch_options(&mut self) {
+        let Some(LaunchOptionsChange::Confirming { edit, .. }) = self.launch_options_change.take()
+        else {
+            return;
+        };
+        let Some(game) = self.selected_game() else {
+            return;
+        };
+        self.status_message = match edit.apply() {
+            Ok(backup) => {
+                let backup = backup
+                    .map(|path| format!(" Backup: {}.", path.display()))
+                    .unwrap_or_default();
+                format!(
+                    "Set the launch options of {} for {}.{backup}",
+                    game.name,
+                    edit.account.display_name()
+                )
+            }
+            Err(error) => format!("Could not write {}: {error}", edit.path.display()),
+        };
+        self.launch_options = None;
+    }
+
+    /// Opens the tool picker for the selected game.
+    fn start_tool_change(&mut self) {
+        let Some(game) = self.selected_game() else {
+            return;
+        };
+        let mut tools: Vec<Tool> = Vec::new();
+        for tool in self
+            .installation_tools
+            .get(&game.installation)
+            .into_iter()
+            .flatten()
+        {
+            if !tools.iter().any(|known| known.name == tool.name) {
+                tools.push(tool.clone());
+            }
+        }
+        if tools.is_empty() {
+            self.status_message = "No compatibility tools are installed.".to_string();
+            return;
+        }
+        let mut state = TableState::default();
+        state.select(Some(0));
+        self.tool_change = Some(ToolChange::Picking { tools, state });
+    }
+
+    /// Prepares the edit for the picked tool and asks for confirmation.
+    fn pick_tool(&mut self) {
+        let Some(ToolChange::Picking { tools, state }) = &self.tool_change else {
+            return;
+        };
+        let Some(tool) = state.selected().and_then(|i| tools.get(i)).cloned() else {
+            return;
+        };
+        let Some(game) = self.selected_game() else {
+            return;
+        };
+        match map_compat_tool(&game.installation, game.app_id, &tool.name) {
+            Ok(edit) if edit.is_unchanged() => {
+                self.status_message = format!("{} already uses {}.", game.name, tool.name);
+                self.tool_change = None;
+            }
+            Ok(edit) => {
+                self.tool_change = Some(ToolChange::Confirming {
+                    tool,
+                    edit,
+                    steam_running: steam_running(),
+                });
+            }
+            Err(error) => {
+                self.status_message = format!("Could not change the tool: {error}");
+                self.tool_change = None;
+            }
+        }
+    }
+
+    /// Writes the confirmed edit and updates the selected game to match.
+    fn confirm_tool_change(&mut self) {
+        let Some(ToolChange::Confirming { tool, edit, .. }) = self.tool_change.take() else {
+            return;
+        };
+        let Some(i) = self
+            .state
+            .selected()
+            .and_then(|i| self.filtered_items.get(i).copied())
+        else {
+            return;
+        };
+        let game = &mut self.items[i];
+        self.status_message = match edit.apply() {
+            Ok(backup) => {
+                game.compat_tool = Some(tool.name.clone());
+                game.compat_tool_path = Some(tool.path.clone());
+                let backup = backup
+                    .map(|path| format!(" Backup: {}.", path.display()))
+                    .unwrap_or_default();
+                format!("{} now uses {}.{backup}", game.name, tool.name)
+            }
+            Err(error) => format!("Could not write {}: {error}", edit.path.display()),
+        };
+    }
+
+    /// Asks to confirm clearing the selected game's shader cache.
+    fn request_shader_cache_clear(&mut self) {
+        let Some(game) = self.selected_game() else {
+            return;
+        };
+        let Some(path) = game.shader_cache_path.clone() else {
+            self.status_message = format!("{} has no shader cache.", game.name);
+            return;
+        };
+        let size = match game.disk_usage {
+            Some(usage) => usage.shader_cache,
+            None => dir_size(&path),
+        };
+        self.shader_cache_clear = Some(ShaderCacheClear {
+            path,
+            size,
+            steam_running: steam_running(),
+        });
+    }
+
+    /// Deletes the selected game's shader cache.
+    fn confirm_shader_cache_clear(&mut self) {
+        let Some(clear) = self.shader_cache_clear.take() else {
+            return;
+        };
+        let Some(i) = self
+            .state
+            .selected()
+            .and_then(|i| self.filtered_items.get(i).copied())
+        else {
+            return;
+        };
+        let game = &mut self.items[i];
+        self.status_message = match game.clear_shader_cache() {
+            Ok(()) => format!(
+                "Cleared the shader cache of {} ({}).",
+                game.name,
+                format_size(clear.size)
+            ),
+            Err(error) => format!("Could not delete {}: {error}", clear.path.display()),
+        };
+    }
+
+    /// Moves the picker selection by `step`, wrapping around.
+    fn move_tool_pick(&mut self, step: isize) {
+        if let Some(ToolChange::Picking { tools, state }) = &mut self.tool_change {
+            let len = tools.len() as isize;
+            let i = state.selected().unwrap_or(0) as isize;
+            state.select(Some((i + step).rem_euclid(len) as usize));
+        }
+    }
+
+    /// Measured size per library; shortcuts are counted under "Non-Steam".
+    fn library_totals(&self) -> BTreeMap<String, u64> {
+        let mut totals = BTreeMap::new();
+        for game in &self.items {
+            let library = match &game.library {
+                Some(path) => path.display().to_string(),
+                None => "Non-Steam".to_string(),
+            };
+            let size = game.disk_usage.map_or(0, |usage| usage.total());
+            *totals.entry(library).or_insert(0) += size;
+        }
+        totals
+    }
+}
+
+/// Names installation `i` by its kind, adding the path when another
+/// installation has the same kind.
+fn installation_label(installations: &[Installation], i: usize) -> String {
+    let installation = &installations[i];
+    let same_kind = installations
+        .iter()
+        .filter(|other| other.kind == installation.kind)
+        .count();
+    if same_kind > 1 {
+        format!("{} {}", installation.kind, installation.path.display())
+    } else {
+        installation.kind.to_string()
+    }
+}
+
+/// Measures every game on a separate thread so the UI stays responsive.
+fn measure_in_background(items: &[Game]) -> Receiver<(usize, DiskUsage)> {
+    let (sender, receiver) = mpsc::channel();
+    let games = items.to_vec();
+    thread::spawn(move || {
+        for (i, game) in games.iter().enumerate() {
+            if sender.send((i, disk_usage(game))).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+/// Measures folders on a separate thread, sending each path with its size.
+fn measure_dirs_in_background(paths: Vec<PathBuf>) -> Receiver<(PathBuf, u64)> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for path in paths {
+            let size = dir_size(&path);
+            if sender.send((path, size)).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+/// A size column cell: blank when the game has no such folder, `…` while measuring.
+fn size_cell(game: &Game, folder: Folder, size: impl Fn(DiskUsage) -> u64) -> String {
+    if game.folder(folder).is_none() {
+        return String::new();
+    }
+    match game.disk_usage {
+        Some(usage) => format_size(size(usage)),
+        None => "…".to_string(),
+    }
+}
+
+/// The mapped tool's name, in red when it is not installed.
+fn tool_cell(game: &Game, theme: &Theme) -> Cell<'static> {
+    let color = if game.compat_tool_missing() {
+        Color::Red
+    } else {
+        theme.text
+    };
+    Cell::from(Span::styled(
+        game.compat_tool.clone().unwrap_or_default(),
+        Style::default().fg(color),
+    ))
+}
+
+/// A `name: value` line of a details pane.
+fn field(theme: &Theme, name: &str, value: String) -> Line<'static> {
+    Line::from(vec![
+        Span::styled(format!("{name}: "), Style::default().fg(theme.label)),
+        Span::styled(value, Style::default().fg(theme.text)),
+    ])
+}
+
+fn details(
+    game: &Game,
+    launch_options: &[LaunchOptions],
+    userdata: &[UserdataFolder],
+    theme: &Theme,
+) -> Vec<Line<'static>> {
+    let label = Style::default().fg(theme.label);
+    let folder = |name: &str, path: Option<&Path>| match path {
+        Some(path) => {
+            let (state, color) = if path.is_dir() {
+                ("exists", Color::Green)
+            } else {
+                ("missing", Color::Red)
+            };
+            Line::from(vec![
+                Span::styled(format!("{name}: "), label),
+                Span::styled(path.display().to_string(), Style::default().fg(theme.text)),
+                Span::styled(format!(" ({state})"), Style::default().fg(color)),
+            ])
+        }
+        None => field(theme, name, "none".to_string()),
+    };
+    let or_unknown = |value: Option<String>| value.unwrap_or_else(|| "unknown".to_string());
+
+    let mut lines = vec![
+        Line::from(Span::styled(
+            game.name.clone(),
+            Style::default().fg(theme.text).add_modifier(Modifier::BOLD),
+        )),
+        field(theme, "App ID", game.app_id.to_string()),
+        field(
+            theme,
+            "Kind",
+            match game.owners.len() {
+                _ if !game.is_non_steam() => "Steam".to_string(),
+                0 => "Non-Steam shortcut".to_string(),
+                1 => format!("Non-Steam shortcut of {}", game.owner_names()),
+                count => format!(
+                    "Non-Steam shortcut of {} (shared by {count} accounts)",
+                    game.owner_names()
+                ),
+            },
+        ),
+        folder("Install", Some(&game.install_path)),
+        folder("Prefix", game.prefix_path.as_deref()),
+        folder("Shader cache", game.shader_cache_path.as_deref()),
+        field(
+            theme,
+            "Installation",
+            game.installation.display().to_string(),
+        ),
+        field(
+            theme,
+            "Library",
+            or_unknown(game.library.as_ref().map(|path| path.display().to_string())),
+        ),
+        compat_tool_line(game, theme),
+        field(
+            theme,
+            "Size",
+            or_unknown(game.size_on_disk.map(format_size)),
+        ),
+        field(
+            theme,
+            "Measured",
+            match game.disk_usage {
+                Some(usage) => format!(
+                    "{} (install {}, compatdata {}, shader cache {})",
+                    format_size(usage.total()),
+                    format_size(usage.install),
+                    format_size(usage.compat_data),
+                    format_size(usage.shader_cache)
+                ),
+                None => "measuring…".to_string(),
+            },
+        ),
+        field(
+            theme,
+            "Build ID",
+            or_unknown(game.build_id.map(|id| id.to_string())),
+        ),
+        field(
+            theme,
+            "Last updated",
+            or_unknown(game.last_updated.map(format_timestamp)),
+        ),
+        field(
+            theme,
+            "Last played",
+            game.last_played
+                .map(format_timestamp)
+                .unwrap_or_else(|| "never".to_string()),
+        ),
+        field(
+            theme,
+            "State",
+            if game.state_flags.is_empty() {
+                "unknown".to_string()
+            } else {
+                game.state_flags.join(", ")
+            },
+        ),
+    ];
+    if launch_options.is_empty() {
+        lines.push(field(theme, "Launch options", "none".to_string()));
+    } else {
+        lines.push(Line::from(Span::styled("Launch options:", label)));
+        lines.extend(launch_options.iter().map(|options| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{}: ", options.account.display_name()),
+                    Style::default().fg(theme.text),
+                ),
+                Span::styled(options.options.clone(), Style::default().fg(theme.dim)),
+            ])
+        }));
+    }
+    if userdata.is_empty() {
+        lines.push(field(theme, "Userdata", "none".to_string()));
+    } else {
+        lines.push(Line::from(Span::styled(
+            "Userdata (press the number to open):",
+            label,
+        )));
+        // Only the first nine can be opened with a key
+        lines.extend(userdata.iter().take(9).enumerate().map(|(i, folder)| {
+            Line::from(vec![
+                Span::styled(
+                    format!(
+                        "{} {} ({}): ",
+                        i + 1,
+                        folder.account.display_name(),
+                        folder.kind.as_str()
+                    ),
+                    Style::default().fg(theme.text),
+                ),
+                Span::styled(
+                    folder.path.display().to_string(),
+                    Style::default().fg(theme.dim),
+                ),
+            ])
+        }));
+    }
+    lines
+}
+
+/// The mapped tool and the folder it resolved to, in red when it is not installed.
+fn compat_tool_line(game: &Game, theme: &Theme) -> Line<'static> {
+    let label = Span::styled("Compat tool: ", Style::default().fg(theme.label));
+    let text = Style::default().fg(theme.text);
+    match (&game.compat_tool, &game.compat_tool_path) {
+        (None, _) => Line::from(vec![label, Span::styled("none", text)]),
+        (Some(tool), Some(path)) => Line::from(vec![
+            label,
+            Span::styled(tool.clone(), text),
+            Span::styled(
+                format!(" ({})", path.display()),
+                Style::default().fg(theme.dim),
+            ),
+        ]),
+        (Some(tool), None) => Line::from(vec![
+            label,
+            Span::styled(tool.clone(), text),
+            Span::styled(" (not installed)", Style::default().fg(Color::Red)),
+        ]),
+    }
+}
+
+/// The tools list: unused tools are highlighted as candidates for removal.
+fn tools_table(app: &App, keys: Keys, theme: &Theme) -> Table<'static> {
+    let rows: Vec<Row> = app
+        .tools
+        .iter()
+        .zip(&app.tool_sizes)
+        .map(|(tool, size)| {
+            let games = app.tool_games(tool);
+            let (used, color) = if tool.default {
+                ("default".to_string(), theme.text)
+            } else if games.is_empty() {
+                ("unused".to_string(), Color::Yellow)
+            } else {
+                (games.len().to_string(), theme.text)
+            };
+            Row::new(vec![
+                Cell::from(tool.display_name.clone()),
+                Cell::from(match tool.kind {
+                    ToolKind::Official => "official",
+                    ToolKind::Custom => "custom",
+                }),
+                Cell::from(tool.version.clone().unwrap_or_default()),
+                Cell::from(size.map_or("…".to_string(), format_size)),
+                Cell::from(used),
+            ])
+            .style(Style::default().fg(color))
+        })
+        .collect();
+    let title = format!(
+        "Compatibility tools ({}, {} unused, Enter to open, {} for games, {} to quit)",
+        app.tools.len(),
+        app.tools
+            .iter()
+            .filter(|tool| app.tool_unused(tool))
+            .count(),
+        keys.tools,
+        keys.quit
+    );
+    let header = Row::new(vec!["Name", "Kind", "Version", "Size", "Games"]).style(
+        Style::default()
+            .fg(theme.label)
+            .add_modifier(Modifier::BOLD),
+    );
+    Table::new(
+        rows,
+        [
+            Constraint::Min(20),
+            Constraint::Length(9),
+            Constraint::Length(20),
+            Constraint::Length(10),
+            Constraint::Length(8),
+        ],
+    )
+    .header(header)
+    .block(Block::default().borders(Borders::ALL).title(title))
+    .highlight_style(Style::default().bg(theme.selected))
+    .highlight_symbol(">> ")
+}
+
+fn tool_details(tool: &Tool, games: &[&Game], theme: &Theme) -> Vec<Line<'static>> {
+    let label = Style::default().fg(theme.label);
+    let text = Style::default().fg(theme.text);
+    let mut lines = vec![
+        Line::from(Span::styled(
+            tool.display_name.clone(),
+            text.add_modifier(Modifier::BOLD),
+        )),
+        field(theme, "Name", tool.name.clone()),
+        field(theme, "Folder", tool.path.display().to_string()),
+        field(
+            theme,
+            "Version",
+            tool.version
+                .clone()
+                .unwrap_or_else(|| "unknown".to_string()),
+        ),
+    ];
+    if tool.default {
+        lines.push(field(
+            theme,
+            "Default",
+            "used for games without a mapping".to_string(),
+        ));
+    }
+    if games.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "No games are mapped to this tool.",
+            Style::default().fg(Color::Yellow),
+        )));
+    } else {
+        lines.push(Line::from(Span::styled("Games:", label)));
+        for game in games {
+            lines.push(Line::from(Span::styled(
+                format!("  {} ({})", game.name, game.app_id),
+                text,
+            )));
+        }
+    }
+    lines
+}
+
+/// The selected game's backups, newest first.
+fn backups_table(app: &App, keys: Keys, theme: &Theme) -> Table<'static> {
+    let backups = app.game_backups();
+    let rows: Vec<Row> = backups
+        .iter()
+        .map(|backup| {
+            let (kind, contents) = match backup {
+                GameBackup::Prefix(backup) => {
+                    ("prefix", backup.compat_tool.clone().unwrap_or_default())
+                }
+                GameBackup::Saves(backup) => ("saves", format!("{} folders", backup.folders.len())),
+            };
+            Row::new(vec![
+                Cell::from(format_timestamp(backup.created())),
+                Cell::from(kind),
+                Cell::from(contents),
+                Cell::from(
+                    backup
+                        .size(&app.save_backup_sizes)
+                        .map_or("…".to_string(), format_size),
+                ),
+            ])
+            .style(Style::default().fg(theme.text))
+        })
+        .collect();
+    let name = app
+        .selected_game()
+        .map(|game| game.name.clone())
+        .unwrap_or_default();
+    let title = format!(
+        "Backups of {name} ({}, Enter to restore, {} for games, {} to quit)",
+        backups.len(),
+        keys.backups,
+        keys.quit
+    );
+    let header = Row::new(vec!["Created", "Kind", "Tool or folders", "Size"]).style(
+        Style::default()
+            .fg(theme.label)
+            .add_modifier(Modifier::BOLD),
+    );
+    Table::new(
+        rows,
+        [
+            Constraint::Length(20),
+            Constraint::Length(8),
+            Constraint::Min(16),
+            Constraint::Length(10),
+        ],
+    )
+    .header(header)
+    .block(Block::default().borders(Borders::ALL).title(title))
+    .highlight_style(Style::default().bg(theme.selected))
+    .highlight_symbol(">> ")
+}
+
+fn backup_details(backup: &GameBackup, game: &Game, theme: &Theme) -> Vec<Line<'static>> {
+    match backup {
+        GameBackup::Prefix(backup) => prefix_backup_details(backup, game, theme),
+        GameBackup::Saves(backup) => save_backup_details(backup, theme),
+    }
+}
+
+fn save_backup_details(backup: &SaveBackup, theme: &Theme) -> Vec<Line<'static>> {
+    let label = Style::default().fg(theme.label);
+    let text = Style::default().fg(theme.text);
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!("Saves of {}", backup.name),
+            text.add_modifier(Modifier::BOLD),
+        )),
+        field(theme, "App ID", backup.app_id.to_string()),
+        field(theme, "Created", format_timestamp(backup.created)),
+        field(theme, "Folder", backup.dir.display().to_string()),
+        Line::from(""),
+        Line::from(Span::styled("Restores to:", label)),
+    ];
+    lines.extend(backup.folders.iter().map(|folder| {
+        Line::from(Span::styled(
+            folder.original.display().to_string(),
+            Style::default().fg(theme.dim),
+        ))
+    }));
+    lines
+}
+
+fn prefix_backup_details(backup: &Backup, game: &Game, theme: &Theme) -> Vec<Line<'static>> {
+    let text = Style::default().fg(theme.text);
+    let mut lines = vec![
+        Line::from(Span::styled(
+            backup.name.clone(),
+            text.add_modifier(Modifier::BOLD),
+        )),
+        field(theme, "App ID", backup.app_id.to_string()),
+        field(theme, "Created", format_timestamp(backup.created)),
+        field(
+            theme,
+            "Compat tool",
+            backup
+                .compat_tool
+                .clone()
+                .unwrap_or_else(|| "none".to_string()),
+        ),
+        field(theme, "Archive", backup.archive.display().to_string()),
+    ];
+    if game.compat_tool != backup.compat_tool {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            format!(
+                "The game now uses {}; restoring does not change its tool.",
+                game.compat_tool.as_deref().unwrap_or("no tool")
+            ),
+            Style::default().fg(Color::Yellow),
+        )));
+    }
+    lines
+}
+
+/// Asks to confirm replacing the selected game's prefix with a backup.
+fn draw_restore_confirmation(
+    f: &mut ratatui::Frame,
+    restore: &Restore,
+    game: &Game,
+    theme: &Theme,
+) {
+    let (question, paths) = match &restore.backup {
+        GameBackup::Prefix(backup) => (
+            format!(
+                "Replace the prefix of {} with the backup from {}? The current prefix is deleted.",
+                game.name,
+                format_timestamp(backup.created)
+            ),
+            vec![backup.archive.clone()],
+        ),
+        GameBackup::Saves(backup) => (
+            format!(
+                "Replace the saves of {} with the ones from {}? These folders are overwritten:",
+                game.name,
+                format_timestamp(backup.created)
+            ),
+            backup
+                .folders
+                .iter()
+                .map(|folder| folder.original.clone())
+                .collect(),
+        ),
+    };
+    let mut lines = vec![
+        Line::from(Span::styled(question, Style::default().fg(theme.text))),
+        Line::from(""),
+    ];
+    lines.extend(paths.iter().map(|path| {
+        Line::from(Span::styled(
+            path.display().to_string(),
+            Style::default().fg(theme.dim),
+        ))
+    }));
+    if restore.steam_running {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "Steam is running; make sure the game is not.",
+            Style::default().fg(Color::Red),
+        )));
+    }
+    render_confirm(f, "Restore backup", lines);
+}
+
+/// The save folders of the selected game, most recently changed first.
+fn saves_table(app: &App, keys: Keys, theme: &Theme) -> Table<'static> {
+    let user = app
+        .selected_game()
+        .and_then(|game| game.prefix_path.as_ref())
+        .map(|pfx| pfx.join("drive_c/users/steamuser"));
+    let rows: Vec<Row> = app
+        .saves
+        .iter()
+        .map(|location| {
+            let relative = user
+                .as_ref()
+                .and_then(|user| location.path.strip_prefix(user).ok())
+                .unwrap_or(&location.path);
+            Row::new(vec![
+                Cell::from(relative.display().to_string()),
+                Cell::from(format_size(location.size)),
+                Cell::from(location.modified.map(format_timestamp).unwrap_or_default()),
+            ])
+            .style(Style::default().fg(theme.text))
+        })
+        .collect();
+    let name = app
+        .selected_game()
+        .map(|game| game.name.clone())
+        .unwrap_or_default();
+    let title = format!(
+        "Saves of {name} ({}, Enter to open, c to copy the path, b to back up, {} for games, {} to quit)",
+        app.saves.len(),
+        keys.saves,
+        keys.quit
+    );
+    let header = Row::new(vec!["Folder", "Size", "Modified"]).style(
+        Style::default()
+            .fg(theme.label)
+            .add_modifier(Modifier::BOLD),
+    );
+    Table::new(
+        rows,
+        [
+            Constraint::Min(30),
+            Constraint::Length(10),
+            Constraint::Length(20),
+        ],
+    )
+    .header(header)
+    .block(Block::default().borders(Borders::ALL).title(title))
+    .highlight_style(Style::default().bg(theme.selected))
+    .highlight_symbol(">> ")
+}
+
+fn save_details(location: &SaveLocation, theme: &Theme) -> Vec<Line<'static>> {
+    let text = Style::default().fg(theme.text);
+    vec![
+        Line::from(Span::styled(
+            location
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            text.add_modifier(Modifier::BOLD),
+        )),
+        field(theme, "Found in", location.root.to_string()),
+        field(theme, "Folder", location.path.display().to_string()),
+        field(theme, "Size", format_size(location.size)),
+        field(
+            theme,
+            "Modified",
+            location
+                .modified
+                .map_or("unknown".to_string(), format_timestamp),
+        ),
+    ]
+}
+
+/// The orphaned folders, with marked ones flagged for cleanup.
+fn orphans_table(app: &App, keys: Keys, theme: &Theme) -> Table<'static> {
+    let rows: Vec<Row> = app
+        .orphans
+        .iter()
+        .map(|entry| {
+            let color = if entry.marked {
+                Color::Yellow
+            } else {
+                theme.text
+            };
+            Row::new(vec![
+                Cell::from(if entry.marked { "[x]" } else { "[ ]" }),
+                Cell::from(entry.orphan.app_id.to_string()),
+                Cell::from(entry.orphan.kind.as_str()),
+                Cell::from(entry.size.map_or("…".to_string(), format_size)),
+                Cell::from(
+                    entry
+                        .orphan
+                        .modified
+                        .map(format_timestamp)
+                        .unwrap_or_default(),
+                ),
+                Cell::from(entry.orphan.path.display().to_string()),
+            ])
+            .style(Style::default().fg(color))
+        })
+        .collect();
+    let title = format!(
+        "Orphaned folders ({}, Space to mark, d to delete, a to archive, {} for games, {} to quit)",
+        app.orphans.len(),
+        keys.orphans,
+        keys.quit
+    );
+    let header = Row::new(vec!["", "App ID", "Kind", "Size", "Last used", "Folder"]).style(
+        Style::default()
+            .fg(theme.label)
+            .add_modifier(Modifier::BOLD),
+    );
+    Table::new(
+        rows,
+        [
+            Constraint::Length(3),
+            Constraint::Length(12),
+            Constraint::Length(12),
+            Constraint::Length(10),
+            Constraint::Length(20),
+            Constraint::Min(20),
+        ],
+    )
+    .header(header)
+    .block(Block::default().borders(Borders::ALL).title(title))
+    .highlight_style(Style::default().bg(theme.selected))
+    .highlight_symbol(">> ")
+}
+
+fn orphan_details(entry: &OrphanEntry, theme: &Theme) -> Vec<Line<'static>> {
+    let text = Style::default().fg(theme.text);
+    let what = match entry.orphan.kind {
+        OrphanKind::CompatData => "prefix",
+        OrphanKind::ShaderCache => "shader cache",
+    };
+    vec![
+        Line::from(Span::styled(
+            format!("{}/{}", entry.orphan.kind.as_str(), entry.orphan.app_id),
+            text.add_modifier(Modifier::BOLD),
+        )),
+        field(theme, "Folder", entry.orphan.path.display().to_string()),
+        field(
+            theme,
+            "Size",
+            entry.size.map_or("measuring…".to_string(), format_size),
+        ),
+        field(
+            theme,
+            "Last used",
+            entry
+                .orphan
+                .modified
+                .map_or("unknown".to_string(), format_timestamp),
+        ),
+        Line::from(""),
+        Line::from(Span::styled(
+            format!("No installed game or shortcut uses this {what}."),
+            Style::default().fg(theme.dim),
+        )),
+    ]
+}
+
+/// Asks to confirm deleting or archiving the orphan targets.
+fn draw_orphan_confirmation(
+    f: &mut ratatui::Frame,
+    app: &App,
+    action: OrphanAction,
+    theme: &Theme,
+) {
+    let targets = app.orphan_targets();
+    let size: u64 = targets.iter().filter_map(|&i| app.orphans[i].size).sum();
+    let question = match (action, &app.archive_dir) {
+        (OrphanAction::Archive, Some(dir)) => format!(
+            "Archive {} orphaned folders ({}) to {} and delete them?",
+            targets.len(),
+            format_size(size),
+            dir.display()
+        ),
+        _ => format!(
+            "Delete {} orphaned folders ({})? This cannot be undone.",
+            targets.len(),
+            format_size(size)
+        ),
+    };
+    let mut lines = vec![
+        Line::from(Span::styled(question, Style::default().fg(theme.text))),
+        Line::from(""),
+    ];
+    for &i in &targets {
+        lines.push(Line::from(Span::styled(
+            app.orphans[i].orphan.path.display().to_string(),
+            Style::default().fg(theme.dim),
+        )));
+    }
+    let title = match action {
+        OrphanAction::Archive if app.archive_dir.is_some() => "Archive orphans",
+        _ => "Delete orphans",
+    };
+    render_confirm(f, title, lines);
+}
+
+/// Asks to confirm clearing the selected game's shader cache, warning when
+/// Steam is running.
+fn draw_shader_cache_confirmation(
+    f: &mut ratatui::Frame,
+    clear: &ShaderCacheClear,
+    game: &Game,
+    theme: &Theme,
+) {
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!(
+                "Delete the shader cache of {} ({})? Steam rebuilds it the next time the game starts.",
+                game.name,
+                format_size(clear.size)
+            ),
+            Style::default().fg(theme.text),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            clear.path.display().to_string(),
+            Style::default().fg(theme.dim),
+        )),
+    ];
+    if clear.steam_running {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "Steam is running and may be using the cache; quit it first if the game is running.",
+            Style::default().fg(Color::Red),
+        )));
+    }
+    render_confirm(f, "Clear shader cache", lines);
+}
+
+/// Asks to confirm resetting the selected game's prefix, with the choice of
+/// keeping its saves.
+fn draw_reset_confirmation(
+    f: &mut ratatui::Frame,
+    request: &ResetRequest,
+    game: &Game,
+    trash_dir: &Path,
+    theme: &Theme,
+) {
+    let mut lines = vec![
+        Line::from(Span::styled(
+            format!(
+                "Reset the prefix of {}? Its compatdata folder ({}) is moved to {} and Proton creates a new one when the game starts.",
+                game.name,
+                format_size(request.size),
+                trash_dir.display()
+            ),
+            Style::default().fg(theme.text),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            request.compat_data.display().to_string(),
+            Style::default().fg(theme.dim),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            format!(
+                "[{}] Keep drive_c/users/steamuser (k to toggle)",
+                if request.keep_saves { "x" } else { " " }
+            ),
+            Style::default().fg(theme.text),
+        )),
+    ];
+    if request.steam_running {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "Steam is running; make sure the game is not.",
+            Style::default().fg(Color::Red),
+        )));
+    }
+    render_confirm(f, "Reset prefix", lines);
+}
+
+/// Draws a confirmation dialog over the middle of the screen.
+fn render_confirm(f: &mut ratatui::Frame, title: &str, lines: Vec<Line<'static>>) {
+    let area = centered(f.size(), 70, 50);
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("{title} (y to continue, n to cancel)")),
+        ),
+        area,
+    );
+}
+
+/// A rectangle of `percent_x` by `percent_y` of `area`, centered in it.
+fn centered(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
+    let width = area.width * percent_x / 100;
+    let height = area.height * percent_y / 100;
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+/// Draws the tool picker or the confirmation of the `config.vdf` edit.
+fn draw_tool_change(f: &mut ratatui::Frame, change: &mut ToolChange, game: &Game, theme: &Theme) {
+    let area = centered(f.size(), 70, 70);
+    f.render_widget(Clear, area);
+    match change {
+        ToolChange::Picking { tools, state } => {
+            let rows: Vec<Row> = tools
+                .iter()
+                .map(|tool| {
+                    let current = game.compat_tool.as_deref() == Some(tool.name.as_str());
+                    Row::new(vec![
+                        Cell::from(tool.name.clone()),
+                        Cell::from(tool.version.clone().unwrap_or_default()),
+                        Cell::from(if current { "current" } else { "" }),
+                    ])
+                })
+                .collect();
+            let table = Table::new(
+                rows,
+                [
+                    Constraint::Min(20),
+                    Constraint::Length(20),
+                    Constraint::Length(8),
+                ],
+            )
+            .block(Block::default().borders(Borders::ALL).title(format!(
+                "Compatibility tool for {} (Enter to choose, Esc to cancel)",
+                game.name
+            )))
+            .style(Style::default().fg(theme.text))
+            .highlight_style(Style::default().bg(theme.selected))
+            .highlight_symbol(">> ");
+            f.render_stateful_widget(table, area, state);
+        }
+        ToolChange::Confirming {
+            tool,
+            edit,
+            steam_running,
+        } => {
+            let mut lines = Vec::new();
+            if *steam_running {
+                lines.push(Line::from(Span::styled(
+                    "Steam is running and may overwrite this change when it exits.",
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                )));
+                lines.push(Line::from(""));
+            }
+            for line in edit.diff().lines() {
+                let color = match line.chars().next() {
+                    Some('+') => Color::Green,
+                    Some('-') => Color::Red,
+                    Some('@') => theme.label,
+                    _ => theme.text,
+                };
+                lines.push(Line::from(Span::styled(
+                    line.replace('\t', "    "),
+                    Style::default().fg(color),
+                )));
+            }
+            let paragraph =
+                Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(format!(
+                    "Map {} to {}? (y to write with a backup, n to cancel)",
+                    game.name, tool.name
+                )));
+            f.render_widget(paragraph, area);
+        }
+    }
+}
+
+/// Draws the launch options editor or the confirmation of its change.
+fn draw_launch_options_change(
+    f: &mut ratatui::Frame,
+    change: &LaunchOptionsChange,
+    game: &Game,
+    theme: &Theme,
+) {
+    let area = centered(f.size(), 70, 50);
+    f.render_widget(Clear, area);
+    let text = Style::default().fg(theme.text);
+    let (title, lines) = match change {
+        LaunchOptionsChange::Editing {
+            accounts,
+            account,
+            text: options,
+        } => {
+            let mut lines = vec![
+                Line::from(vec![
+                    Span::styled("> ", Style::default().fg(theme.label)),
+                    Span::styled(format!("{options}_"), text),
+                ]),
+                Line::from(""),
+                Line::from(Span::styled(
+                    "Presets (press the key to switch on or off):",
+                    Style::default().fg(theme.label),
+                )),
+            ];
+            lines.extend(LAUNCH_PRESETS.iter().enumerate().map(|(i, preset)| {
+                let mark = if has_preset(options, preset) {
+                    "x"
+                } else {
+                    " "
+                };
+                Line::from(vec![
+                    Span::styled(format!("F{} [{mark}] {}", i + 1, preset.option), text),
+                    Span::styled(
+                        format!("  {}", preset.description),
+                        Style::default().fg(theme.dim),
+                    ),
+                ])
+            }));
+            let switch = if accounts.len() > 1 {
+                ", Tab for the next account"
+            } else {
+                ""
+            };
+            let title = format!(
+                "Launch options of {} for {} (Enter to review{switch}, Esc to cancel)",
+                game.name,
+                accounts[*account].display_name()
+            );
+            (title, lines)
+        }
+        LaunchOptionsChange::Confirming {
+            edit,
+            steam_running,
+        } => {
+            let mut lines = Vec::new();
+            if *steam_running {
+                lines.push(Line::from(Span::styled(
+                    "Steam is running and may overwrite this change when it exits.",
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                )));
+                lines.push(Line::from(""));
+            }
+            lines.extend([
+                Line::from(Span::styled(
+                    edit.path.display().to_string(),
+                    Style::default().fg(theme.dim),
+                )),
+                Line::from(Span::styled(
+                    format!("- {}", edit.old),
+                    Style::default().fg(Color::Red),
+                )),
+                Line::from(Span::styled(
+                    format!("+ {}", edit.new),
+                    Style::default().fg(Color::Green),
+                )),
+            ]);
+            let title = format!(
+                "Set the launch options of {} for {}? (y to write with a backup, n to cancel)",
+                game.name,
+                edit.account.display_name()
+            );
+            (title, lines)
+        }
+    };
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .wrap(Wrap { trim: false });
+    f.render_widget(paragraph, area);
+}
+
+/// Splits `name` into spans, styling the chars at `matched` positions.
+fn highlight_name<'a>(name: &'a str, matched: &[usize], theme: &Theme) -> Vec<Span<'a>> {
+    let normal = Style::default().fg(theme.text);
+    let highlighted = Style::default()
+        .fg(theme.matched)
+        .add_modifier(Modifier::BOLD);
+
+    let mut spans = Vec::new();
+    let mut start = 0;
+    let mut in_match = false;
+    for (i, (byte, _)) in name.char_indices().enumerate() {
+        let is_match = matched.contains(&i);
+        if is_match != in_match && byte > start {
+            let style = if in_match { highlighted } else { normal };
+            spans.push(Span::styled(&name[start..byte], style));
+            start = byte;
+        }
+        in_match = is_match;
+    }
+    let style = if in_match { highlighted } else { normal };
+    spans.push(Span::styled(&name[start..], style));
+    spans
+}
+
+pub fn run(
+    items: Vec<Game>,
+    tools: Vec<Tool>,
+    installation_tools: BTreeMap<PathBuf, Vec<Tool>>,
+    orphans: Vec<Orphan>,
+    installations: Vec<Installation>,
+    opener: Opener,
+    config: &Config,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Setup terminal
+    enable_raw_mode()?;
+    let mut stdout = stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+
+    let mut app = App::new(
+        items,
+        tools,
+        installation_tools,
+        orphans,
+        installations,
+        opener,
+        config,
+    );
+    let theme = app.theme;
+    let keys = app.keys;
+    app.state.select(Some(0));
+
+    loop {
+        app.receive_sizes();
+        app.receive_openings();
+        app.load_launch_options();
+
+        terminal.draw(|f| {
+            let size = f.size();
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(
+                    [
+                        Constraint::Length(3),
+                        Constraint::Length(3),
+                        Constraint::Percentage(94),
+                        Constraint::Length(3),
+                    ]
+                    .as_ref(),
+                )
+                .split(size);
+
+            let search_title = if app.in_search_mode {
+                "Search (type to search, Enter to exit)".to_string()
+            } else {
+                format!("Search (press '{}' to enter search mode)", keys.search)
+            };
+            let search_block = Block::default().borders(Borders::ALL).title(search_title);
+            let search_text = if app.search_query.is_empty() && !app.in_search_mode {
+                "No search query"
+            } else {
+                &app.search_query
+            };
+            let search_paragraph = Paragraph::new(search_text)
+                .block(search_block)
+                .style(Style::default().fg(theme.text));
+
+            let rows: Vec<Row> = app
+                .filtered_items
+                .iter()
+                .map(|&i| {
+                    let game = &app.items[i];
+                    let label = if game.is_non_steam() {
+                        "Non-Steam: "
+                    } else {
+                        ""
+                    };
+                    let matched = match_game(&app.search_query, game)
+                        .map(|m| m.indices)
+                        .unwrap_or_default();
+                    let prefix = if game.prefix_path.is_some() {
+                        " [prefix]"
+                    } else {
+                        ""
+                    };
+                    // Shortcuts belong to accounts rather than libraries
+                    let library = match &game.library {
+                        Some(path) => path.display().to_string(),
+                        None => game.owner_names(),
+                    };
+                    let mut spans = vec![Span::styled(label, Style::default().fg(theme.text))];
+                    spans.extend(highlight_name(&game.name, &matched, &theme));
+                    spans.push(Span::styled(prefix, Style::default().fg(theme.text)));
+                    Row::new(vec![
+                        Cell::from(Line::from(spans)),
+                        Cell::from(game.app_id.to_string()),
+                        tool_cell(game, &theme),
+                        Cell::from(size_cell(game, Folder::Install, |usage| usage.install)),
+                        Cell::from(size_cell(game, Folder::CompatData, |usage| {
+                            usage.compat_data
+                        })),
+                        Cell::from(size_cell(game, Folder::ShaderCache, |usage| {
+                            usage.shader_cache
+                        })),
+                        Cell::from(Span::styled(library, Style::default().fg(theme.dim))),
+                    ])
+                })
+                .collect();
+
+            let installation = if app.installations.len() > 1 {
+                format!(", installation: {}", app.installation_label())
+            } else {
+                String::new()
+            };
+            let account = if app.known_accounts().len() > 1 {
+                format!(", account: {}", app.account_label())
+            } else {
+                String::new()
+            };
+            let list_title = format!(
+                "Games ({}/{}, sort: {} {}{installation}{account}, ↑/↓ to navigate, Enter to open, {} for prefix, {} to quit)",
+                app.filtered_items.len(),
+                app.items.len(),
+                app.sort_key.as_str(),
+                if app.sort_descending { "↓" } else { "↑" },
+                keys.open_prefix,
+                keys.quit
+            );
+            let widths = [
+                Constraint::Min(24),
+                Constraint::Length(10),
+                Constraint::Length(16),
+                Constraint::Length(10),
+                Constraint::Length(10),
+                Constraint::Length(10),
+                Constraint::Percentage(25),
+            ];
+            let header = Row::new(vec![
+                "Name", "App ID", "Tool", "Install", "Prefix", "Shaders", "Library",
+            ])
+            .style(
+                Style::default()
+                    .fg(theme.label)
+                    .add_modifier(Modifier::BOLD),
+            );
+            let list = Table::new(rows, widths)
+                .header(header)
+                .block(Block::default().borders(Borders::ALL).title(list_title))
+                .highlight_style(Style::default().bg(theme.selected))
+                .highlight_symbol(">> ");
+
+            let mut usage_text: Vec<String> = match app.view {
+                View::Games => app
+                    .library_totals()
+                    .into_iter()
+                    .map(|(library, total)| format!("{library}: {}", format_size(total)))
+                    .collect(),
+                View::Tools => app.tool_totals(),
+                View::Orphans => app.orphan_totals(),
+                View::Backups => app.backup_totals(),
+                View::Saves => vec![format!(
+                    "{} save folders: {}",
+                    app.saves.len(),
+                    format_size(app.saves.iter().map(|location| location.size).sum())
+                )],
+            };
+            if app.sizes_pending > 0 {
+                usage_text.push(format!("measuring {} more…", app.sizes_pending));
+            }
+            let usage = Paragraph::new(usage_text.join("  |  "))
+                .block(Block::default().borders(Borders::ALL).title("Disk usage"))
+                .style(Style::default().fg(theme.text));
+
+            let footer = Paragraph::new(app.status_message.as_str())
+                .block(Block::default().borders(Borders::ALL))
+                .style(Style::default().fg(theme.label));
+
+            let details_text = match app.view {
+                View::Games => app
+                    .selected_game()
+                    .map(|game| {
+                        details(
+                            game,
+                            app.selected_launch_options(),
+                            &app.game_userdata(game),
+                            &theme,
+                        )
+                    }),
+                View::Tools => app
+                    .selected_tool()
+                    .map(|tool| tool_details(tool, &app.tool_games(tool), &theme)),
+                View::Orphans => app
+                    .orphans_state
+                    .selected()
+                    .map(|i| orphan_details(&app.orphans[i], &theme)),
+                View::Backups => app
+                    .selected_backup()
+                    .zip(app.selected_game())
+                    .map(|(backup, game)| backup_details(&backup, game, &theme)),
+                View::Saves => app
+                    .selected_save()
+                    .map(|location| save_details(location, &theme)),
+            }
+            .unwrap_or_default();
+            let details_paragraph = Paragraph::new(details_text)
+                .block(Block::default().borders(Borders::ALL).title("Details"))
+                .wrap(Wrap { trim: false });
+
+            let body = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+                .split(chunks[2]);
+
+            f.render_widget(search_paragraph, chunks[0]);
+            f.render_widget(usage, chunks[1]);
+            match app.view {
+                View::Games => f.render_stateful_widget(list, body[0], &mut app.state),
+                View::Tools => {
+                    let tools = tools_table(&app, keys, &theme);
+                    f.render_stateful_widget(tools, body[0], &mut app.tools_state)
+                }
+                View::Orphans => {
+                    let orphans = orphans_table(&app, keys, &theme);
+                    f.render_stateful_widget(orphans, body[0], &mut app.orphans_state)
+                }
+                View::Backups => {
+                    let backups = backups_table(&app, keys, &theme);
+                    f.render_stateful_widget(backups, body[0], &mut app.backups_state)
+                }
+                View::Saves => {
+                    let saves = saves_table(&app, keys, &theme);
+                    f.render_stateful_widget(saves, body[0], &mut app.saves_state)
+                }
+            }
+            f.render_widget(details_paragraph, body[1]);
+            f.render_widget(footer, chunks[3]);
+
+            let game = app
+                .state
+                .selected()
+                .and_then(|i| app.filtered_items.get(i))
+                .map(|&i| &app.items[i]);
+            if let (Some(change), Some(game)) = (&mut app.tool_change, game) {
+                draw_tool_change(f, change, game, &theme);
+            }
+            if let (Some(change), Some(game)) = (&app.launch_options_change, game) {
+                draw_launch_options_change(f, change, game, &theme);
+            }
+            if let (Some(clear), Some(game)) = (&app.shader_cache_clear, game) {
+                draw_shader_cache_confirmation(f, clear, game, &theme);
+            }
+            if let (Some(restore), Some(game)) = (&app.restore, game) {
+                draw_restore_confirmation(f, restore, game, &theme);
+            }
+            if let (Some(request), Some(game), Some(trash_dir)) =
+                (&app.reset, game, &app.trash_dir)
+            {
+                draw_reset_confirmation(f, request, game, trash_dir, &theme);
+            }
+            if let Some(action) = app.orphan_action {
+                draw_orphan_confirmation(f, &app, action, &theme);
+            }
+        })?;
+
+        if crossterm::event::poll(std::time::Duration::from_millis(100))? {
+            if let crossterm::event::Event::Key(key) = crossterm::event::read()? {
+                if let Some(change) = &app.tool_change {
+                    let confirming = matches!(change, ToolChange::Confirming { .. });
+                    match key.code {
+                        crossterm::event::KeyCode::Esc
+                        | crossterm::event::KeyCode::Char('n')
+                        | crossterm::event::KeyCode::Char('q') => app.tool_change = None,
+                        crossterm::event::KeyCode::Char('y') if confirming => {
+                            app.confirm_tool_change()
+                        }
+                        crossterm::event::KeyCode::Enter if !confirming => app.pick_tool(),
+                        crossterm::event::KeyCode::Down => app.move_tool_pick(1),
+                        crossterm::event::KeyCode::Up => app.move_tool_pick(-1),
+                        _ => {}
+                    }
+                } else if let Some(change) = &mut app.launch_options_change {
+                    match (change, key.code) {
+                        (_, crossterm::event::KeyCode::Esc) => app.launch_options_change = None,
+                        (
+                            LaunchOptionsChange::Confirming { .. },
+                            crossterm::event::KeyCode::Char('y'),
+                        ) => app.confirm_launch_options(),
+                        (
+                            LaunchOptionsChange::Confirming { .. },
+                            crossterm::event::KeyCode::Char('n'),
+                        ) => app.launch_options_change = None,
+                        (LaunchOptionsChange::Editing { .. }, crossterm::event::KeyCode::Enter) => {
+                            app.review_launch_options()
+                        }
+                        (LaunchOptionsChange::Editing { .. }, crossterm::event::KeyCode::Tab) => {
+                            app.next_launch_options_account()
+                        }
+                        (
+                            LaunchOptionsChange::Editing { text, .. },
+                            crossterm::event::KeyCode::Backspace,
+                        ) => {
+                            text.pop();
+                        }
+                        (
+                            LaunchOptionsChange::Editing { text, .. },
+                            crossterm::event::KeyCode::Char(c),
+                        ) => text.push(c),
+                        (
+                            LaunchOptionsChange::Editing { text, .. },
+                            crossterm::event::KeyCode::F(n),
+                        ) => {
+                            if let Some(preset) = LAUNCH_PRESETS.get(usize::from(n) - 1) {
+                                *text = toggle_preset(text, preset);
+                            }
+                        }
+                        _ => {}
+                    }
+                } else if app.in_search_mode {
+                    match key.code {
+                        crossterm::event::KeyCode::Enter => app.exit_search_mode(),
+                        crossterm::event::KeyCode::Backspace => {
+                            app.search_query.pop();
+                            app.update_filter();
+                        }
+                        crossterm::event::KeyCode::Char(c) => {
+                            app.search_query.push(c);
+                            app.update_filter();
+                        }
+                        _ => {}
+                    }
+                } else if app.shader_cache_clear.is_some() {
+                    match key.code {
+                        crossterm::event::KeyCode::Char('y') => app.confirm_shader_cache_clear(),
+                        crossterm::event::KeyCode::Char('n') | crossterm::event::KeyCode::Esc => {
+                            app.shader_cache_clear = None
+                        }
+                        _ => {}
+                    }
+                } else if let Some(request) = &mut app.reset {
+                    match key.code {
+                        crossterm::event::KeyCode::Char('y') => app.confirm_reset(),
+                        crossterm::event::KeyCode::Char('k') => {
+                            request.keep_saves = !request.keep_saves
+                        }
+                        crossterm::event::KeyCode::Char('n') | crossterm::event::KeyCode::Esc => {
+                            app.reset = None
+                        }
+                        _ => {}
+                    }
+                } else if app.restore.is_some() {
+                    match key.code {
+                        crossterm::event::KeyCode::Char('y') => app.confirm_restore(),
+                        crossterm::event::KeyCode::Char('n') | crossterm::event::KeyCode::Esc => {
+                            app.restore = None
+                        }
+                        _ => {}
+                    }
+                } else if app.orphan_action.is_some() {
+                    match key.code {
+                        crossterm::event::KeyCode::Char('y') => app.confirm_orphan_action(),
+                        crossterm::event::KeyCode::Char('n') | crossterm::event::KeyCode::Esc => {
+                            app.orphan_action = None
+                        }
+                        _ => {}
+                    }
+                } else if app.view == View::Orphans {
+                    match key.code {
+                        crossterm::event::KeyCode::Char(c) if c == keys.quit => break,
+                        crossterm::event::KeyCode::Char(c) if c == keys.orphans => {
+                            app.toggle_view(View::Orphans)
+                        }
+                        crossterm::event::KeyCode::Char(c) if c == keys.tools => {
+                            app.toggle_view(View::Tools)
+                        }
+                        crossterm::event::KeyCode::Down => app.move_orphan_selection(1),
+                        crossterm::event::KeyCode::Up => app.move_orphan_selection(-1),
+                        crossterm::event::KeyCode::Char(' ') => app.toggle_orphan_mark(),
+                        crossterm::event::KeyCode::Char('d') => {
+                            app.request_orphan_action(OrphanAction::Delete)
+                        }
+                        crossterm::event::KeyCode::Char('a') => {
+                            app.request_orphan_action(OrphanAction::Archive)
+                        }
+                        _ => {}
+                    }
+                } else if app.view == View::Saves {
+                    match key.code {
+                        crossterm::event::KeyCode::Char(c) if c == keys.quit => break,
+                        crossterm::event::KeyCode::Char(c) if c == keys.saves => {
+                            app.toggle_view(View::Saves)
+                        }
+                        crossterm::event::KeyCode::Down => app.move_save_selection(1),
+                        crossterm::event::KeyCode::Up => app.move_save_selection(-1),
+                        crossterm::event::KeyCode::Enter => app.open_selected_save(),
+                        crossterm::event::KeyCode::Char('c') => app.copy_selected_save_path(),
+                        crossterm::event::KeyCode::Char('b') => app.back_up_selected_saves(),
+                        _ => {}
+                    }
+                } else if app.view == View::Backups {
+                    match key.code {
+                        crossterm::event::KeyCode::Char(c) if c == keys.quit => break,
+                        crossterm::event::KeyCode::Char(c) if c == keys.backups => {
+                            app.toggle_view(View::Backups)
+                        }
+                        crossterm::event::KeyCode::Char(c) if c == keys.tools => {
+                            app.toggle_view(View::Tools)
+                        }
+                        crossterm::event::KeyCode::Char(c) if c == keys.orphans => {
+                            app.toggle_view(View::Orphans)
+                        }
+                        crossterm::event::KeyCode::Down => app.move_backup_selection(1),
+                        crossterm::event::KeyCode::Up => app.move_backup_selection(-1),
+                        crossterm::event::KeyCode::Enter => app.request_restore(),
+                        _ => {}
+                    }
+                } else if app.view == View::Tools {
+                    match key.code {
+                        crossterm::event::KeyCode::Char(c) if c == keys.quit => break,
+                        crossterm::event::KeyCode::Char(c) if c == keys.tools => {
+                            app.toggle_view(View::Tools)
+                        }
+                        crossterm::event::KeyCode::Char(c) if c == keys.orphans => {
+                            app.toggle_view(View::Orphans)
+                        }
+                        crossterm::event::KeyCode::Down => app.next_tool(),
+                        crossterm::event::KeyCode::Up => app.previous_tool(),
+                        crossterm::event::KeyCode::Enter => app.open_selected_tool(),
+                        _ => {}
+                    }
+                } else {
+                    match key.code {
+                        crossterm::event::KeyCode::Char(c) if c == keys.quit => break,
+                        crossterm::event::KeyCode::Char(c) if c == keys.tools => {
+                            app.toggle_view(View::Tools)
+                        }
+                        crossterm::event::KeyCode::Char(c) if c == keys.orphans => {
+                            app.toggle_view(View::Orphans)
+                        }
+                        crossterm::event::KeyCode::Char(c) if c == keys.search => {
+                            app.enter_search_mode()
+                        }
+                        crossterm::event::KeyCode::Down => app.next(),
+                        crossterm::event::KeyCode::Up => app.previous(),
+                        crossterm::event::KeyCode::Enter => app.open_selected(Folder::Install),
+                        crossterm::event::KeyCode::Char(c) if c == keys.open_prefix => {
+                            app.open_selected(Folder::Prefix)
+                        }
+                        crossterm::event::KeyCode::Char(c) if c == keys.sort => {
+                            app.cycle_sort_key()
+                        }
+                        crossterm::event::KeyCode::Char(c) if c == keys.reverse_sort => {
+                            app.toggle_sort_direction()
+                        }
+                        crossterm::event::KeyCode::Char(c) if c == keys.installation => {
+                            app.cycle_installation_filter()
+                        }
+                        crossterm::event::KeyCode::Char(c) if c == keys.account => {
+                            app.cycle_account_filter()
+                        }
+                        crossterm::event::KeyCode::Char(c) if c == keys.change_tool => {
+                            app.start_tool_change()
+                        }
+                        crossterm::event::KeyCode::Char(c) if c == keys.launch_options => {
+                            app.start_launch_options_edit()
+                        }
+                        crossterm::event::KeyCode::Char(c) if c == keys.clear_shader_cache => {
+                            app.request_shader_cache_clear()
+                        }
+                        crossterm::event::KeyCode::Char(c) if c == keys.backup_prefix => {
+                            app.back_up_selected()
+                        }
+                        crossterm::event::KeyCode::Char(c) if c == keys.backups => {
+                            app.toggle_view(View::Backups)
+                        }
+                        crossterm::event::KeyCode::Char(c) if c == keys.reset_prefix => {
+                            app.request_reset()
+                        }
+                        crossterm::event::KeyCode::Char(c) if c == keys.saves => {
+                            app.toggle_view(View::Saves)
+                        }
+                        crossterm::event::KeyCode::Char(c @ '1'..='9') => {
+                            app.open_userdata(c as usize - '1' as usize)
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    // Restore terminal
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+
+    Ok(())
+}
+
+/// Asks which of several Steam installations to scan, offering all of them
+/// first. Returns `None` when the user quits without choosing.
+pub fn pick_installations(
+    installations: &[Installation],
+) -> Result<Option<Vec<Installation>>, Box<dyn std::error::Error>> {
+    enable_raw_mode()?;
+    let mut stdout = stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+
+    // Row 0 is "all", the rest are the installations in order
+    let count = installations.len() + 1;
+    let mut state = TableState::default();
+    state.select(Some(0));
+    let choice = loop {
+        terminal.draw(|f| {
+            let all = Row::new(vec![
+                Cell::from("all"),
+                Cell::from(format!("All {} installations", installations.len())),
+            ]);
+            let rows = std::iter::once(all).chain(installations.iter().map(|installation| {
+                Row::new(vec![
+                    Cell::from(installation.kind.to_string()),
+                    Cell::from(installation.path.display().to_string()),
+                ])
+            }));
+            let table = Table::new(rows, [Constraint::Length(8), Constraint::Min(20)])
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title("Several Steam installations found (Enter to choose, q to quit)"),
+                )
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+            f.render_stateful_widget(table, f.size(), &mut state);
+        })?;
+
+        if let crossterm::event::Event::Key(key) = crossterm::event::read()? {
+            let selected = state.selected().unwrap_or(0);
+            match key.code {
+                crossterm::event::KeyCode::Char('q') | crossterm::event::KeyCode::Esc => {
+                    break None
+                }
+                crossterm::event::KeyCode::Enter if selected == 0 => {
+                    break Some(installations.to_vec())
+                }
+                crossterm::event::KeyCode::Enter => {
+                    break Some(vec![installations[selected - 1].clone()])
+                }
+                crossterm::event::KeyCode::Down => state.select(Some((selected + 1) % count)),
+                crossterm::event::KeyCode::Up => state.select(Some((selected + count - 1) % count)),
+                _ => {}
+            }
+        }
+    };
+
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+
+    Ok(choice)
+}
@@ -0,0 +1,56 @@
+use std::fs;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::{Folder, Game};
+
+/// Bytes used on disk by the folders of a [`Game`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct DiskUsage {
+    pub install: u64,
+    /// The whole `compatdata/<app_id>` folder, not just the prefix.
+    pub compat_data: u64,
+    pub shader_cache: u64,
+}
+
+impl DiskUsage {
+    pub fn total(&self) -> u64 {
+        self.install + self.compat_data + self.shader_cache
+    }
+}
+
+/// Measures every folder of `game`. This walks the whole tree, so it can be slow.
+///
+/// Relative folders, such as a shortcut started from `./`, are not measured.
+pub fn disk_usage(game: &Game) -> DiskUsage {
+    let size = |folder| {
+        game.folder(folder)
+            .filter(|path| path.is_absolute())
+            .map_or(0, |path| dir_size(&path))
+    };
+    DiskUsage {
+        install: size(Folder::Install),
+        compat_data: size(Folder::CompatData),
+        shader_cache: size(Folder::ShaderCache),
+    }
+}
+
+/// Total size of the files under `path`. Symlinks are not followed and unreadable
+/// entries are skipped.
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(metadata) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| dir_size(&entry.path()))
+        .sum()
+}
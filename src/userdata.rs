@@ -0,0 +1,104 @@
+//! Per-account data under `userdata/<account id>`.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use keyvalues_parser::Vdf;
+
+use crate::vdf::{self, BinaryValue};
+
+/// The `userdata/<account id>` folders of every account that used this installation.
+pub(crate) fn user_dirs(steam_root: &Path) -> Vec<(u32, PathBuf)> {
+    let Ok(entries) = fs::read_dir(steam_root.join("userdata")) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<(u32, PathBuf)> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let account_id = entry.file_name().to_str()?.parse().ok()?;
+            // `userdata/0` is used for anonymous logins
+            (account_id != 0 && entry.path().is_dir()).then(|| (account_id, entry.path()))
+        })
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+/// The parsed `config/shortcuts.vdf` of one account.
+pub(crate) fn read_shortcuts(user_dir: &Path) -> Option<BinaryValue> {
+    let bytes = fs::read(user_dir.join("config").join("shortcuts.vdf")).ok()?;
+    let root = vdf::parse_binary(&bytes)?;
+    root.get("shortcuts").cloned()
+}
+
+/// When each app was last played by any account, in seconds since the Unix epoch.
+pub(crate) fn last_played(steam_root: &Path) -> HashMap<u32, u64> {
+    let mut last_played = HashMap::new();
+    let mut record = |app_id: u32, time: u64| {
+        if time > 0 {
+            let entry = last_played.entry(app_id).or_insert(time);
+            *entry = (*entry).max(time);
+        }
+    };
+
+    for (_, user_dir) in user_dirs(steam_root) {
+        let localconfig = user_dir.join("config").join("localconfig.vdf");
+        if let Ok(text) = fs::read_to_string(&localconfig) {
+            if let Ok(config) = Vdf::parse(&text) {
+                let apps = config.value.get_obj().and_then(|obj| {
+                    vdf::get_path(obj, &["Software", "Valve", "Steam", "apps"])?.get_obj()
+                });
+                for (app_id, values) in apps.into_iter().flat_map(|apps| apps.iter()) {
+                    let time = values
+                        .first()
+                        .and_then(|app| app.get_obj())
+                        .and_then(|app| vdf::get(app, "LastPlayed"))
+                        .and_then(|time| time.get_str())
+                        .and_then(|time| time.parse().ok());
+                    if let (Ok(app_id), Some(time)) = (app_id.parse(), time) {
+                        record(app_id, time);
+                    }
+                }
+            }
+        }
+
+        if let Some(shortcuts) = read_shortcuts(&user_dir) {
+            for (_, shortcut) in shortcuts.entries() {
+                let app_id = shortcut.get("appid").and_then(BinaryValue::as_int);
+                let time = shortcut.get("LastPlayTime").and_then(BinaryValue::as_int);
+                if let (Some(app_id), Some(time)) = (app_id, time) {
+                    record(app_id, u64::from(time));
+                }
+            }
+        }
+    }
+
+    last_played
+}
+
+/// The account IDs with each shortcut's app ID in their `shortcuts.vdf`.
+pub(crate) fn shortcut_owners(steam_root: &Path) -> HashMap<u32, Vec<u32>> {
+    let mut owners: HashMap<u32, Vec<u32>> = HashMap::new();
+    for (account_id, user_dir) in user_dirs(steam_root) {
+        let Some(shortcuts) = read_shortcuts(&user_dir) else {
+            continue;
+        };
+        for (_, shortcut) in shortcuts.entries() {
+            if let Some(app_id) = shortcut.get("appid").and_then(BinaryValue::as_int) {
+                owners.entry(app_id).or_default().push(account_id);
+            }
+        }
+    }
+    owners
+}
+
+/// The `userdata/<account id>/<app_id>/remote` folders holding the Steam Cloud
+/// files of `app_id`, for every account that has them.
+pub(crate) fn remote_dirs(steam_root: &Path, app_id: u32) -> Vec<(u32, PathBuf)> {
+    user_dirs(steam_root)
+        .into_iter()
+        .map(|(account_id, dir)| (account_id, dir.join(app_id.to_string()).join("remote")))
+        .filter(|(_, dir)| dir.is_dir())
+        .collect()
+}
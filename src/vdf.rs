@@ -0,0 +1,566 @@
+//! Helpers for the text and binary VDF files Steam keeps its settings in.
+
+use keyvalues_parser::{Obj, Value};
+
+/// Looks up `key` in `obj`, ignoring ASCII case as Steam does.
+pub(crate) fn get<'a>(obj: &'a Obj<'a>, key: &str) -> Option<&'a Value<'a>> {
+    obj.iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(key))
+        .and_then(|(_, values)| values.first())
+}
+
+/// Follows `keys` down through nested objects.
+pub(crate) fn get_path<'a>(obj: &'a Obj<'a>, keys: &[&str]) -> Option<&'a Value<'a>> {
+    let (last, parents) = keys.split_last()?;
+    let mut obj = obj;
+    for key in parents {
+        obj = get(obj, key)?.get_obj()?;
+    }
+    get(obj, last)
+}
+
+/// A value from a binary VDF file such as `shortcuts.vdf`.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum BinaryValue {
+    Map(Vec<(String, BinaryValue)>),
+    Str(String),
+    Int(u32),
+    Float(f32),
+    Long(u64),
+}
+
+impl BinaryValue {
+    pub(crate) fn get(&self, key: &str) -> Option<&BinaryValue> {
+        match self {
+            Self::Map(entries) => entries
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_int(&self) -> Option<u32> {
+        match self {
+            Self::Int(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn entries(&self) -> &[(String, BinaryValue)] {
+        match self {
+            Self::Map(entries) => entries,
+            _ => &[],
+        }
+    }
+
+    pub(crate) fn entries_mut(&mut self) -> &mut [(String, BinaryValue)] {
+        match self {
+            Self::Map(entries) => entries,
+            _ => &mut [],
+        }
+    }
+
+    /// Sets `key` in a map, replacing the value under any casing of it or
+    /// adding it at the end. Does nothing to other values.
+    pub(crate) fn set(&mut self, key: &str, value: BinaryValue) {
+        if let Self::Map(entries) = self {
+            match entries
+                .iter_mut()
+                .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            {
+                Some((_, old)) => *old = value,
+                None => entries.push((key.to_string(), value)),
+            }
+        }
+    }
+}
+
+const MAP: u8 = 0x00;
+const STR: u8 = 0x01;
+const INT: u8 = 0x02;
+const FLOAT: u8 = 0x03;
+const LONG: u8 = 0x07;
+const END: u8 = 0x08;
+
+/// Parses a binary VDF file into its top level map, or `None` if it is malformed.
+pub(crate) fn parse_binary(bytes: &[u8]) -> Option<BinaryValue> {
+    let mut rest = bytes;
+    let entries = parse_map(&mut rest)?;
+    Some(BinaryValue::Map(entries))
+}
+
+/// Writes a top level map back in the format [`parse_binary`] reads.
+pub(crate) fn write_binary(root: &BinaryValue) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_map(&mut out, root.entries());
+    out
+}
+
+fn write_map(out: &mut Vec<u8>, entries: &[(String, BinaryValue)]) {
+    for (key, value) in entries {
+        let kind = match value {
+            BinaryValue::Map(_) => MAP,
+            BinaryValue::Str(_) => STR,
+            BinaryValue::Int(_) => INT,
+            BinaryValue::Float(_) => FLOAT,
+            BinaryValue::Long(_) => LONG,
+        };
+        out.push(kind);
+        out.extend_from_slice(key.as_bytes());
+        out.push(0);
+        match value {
+            BinaryValue::Map(children) => write_map(out, children),
+            BinaryValue::Str(s) => {
+                out.extend_from_slice(s.as_bytes());
+                out.push(0);
+            }
+            BinaryValue::Int(n) => out.extend_from_slice(&n.to_le_bytes()),
+            BinaryValue::Float(n) => out.extend_from_slice(&n.to_le_bytes()),
+            BinaryValue::Long(n) => out.extend_from_slice(&n.to_le_bytes()),
+        }
+    }
+    out.push(END);
+}
+
+fn parse_map(rest: &mut &[u8]) -> Option<Vec<(String, BinaryValue)>> {
+    let mut entries = Vec::new();
+    loop {
+        let (&kind, tail) = rest.split_first()?;
+        *rest = tail;
+        if kind == END {
+            return Some(entries);
+        }
+        let key = take_str(rest)?;
+        let value = match kind {
+            MAP => BinaryValue::Map(parse_map(rest)?),
+            STR => BinaryValue::Str(take_str(rest)?),
+            INT => BinaryValue::Int(u32::from_le_bytes(take(rest)?)),
+            FLOAT => BinaryValue::Float(f32::from_le_bytes(take(rest)?)),
+            LONG => BinaryValue::Long(u64::from_le_bytes(take(rest)?)),
+            _ => return None,
+        };
+        entries.push((key, value));
+    }
+}
+
+fn take<const N: usize>(rest: &mut &[u8]) -> Option<[u8; N]> {
+    let bytes = rest.get(..N)?.try_into().ok()?;
+    *rest = &rest[N..];
+    Some(bytes)
+}
+
+fn take_str(rest: &mut &[u8]) -> Option<String> {
+    let end = rest.iter().position(|&b| b == 0)?;
+    let s = String::from_utf8_lossy(&rest[..end]).into_owned();
+    *rest = &rest[end + 1..];
+    Some(s)
+}
+
+/// A key and its value in a text VDF file, with byte offsets into the text so
+/// single values can be changed without reformatting the rest of the file.
+#[derive(Debug)]
+struct TextEntry {
+    key: String,
+    value: TextValue,
+}
+
+#[derive(Debug)]
+enum TextValue {
+    /// A string token spanning `start..end`, quotes included.
+    Str { start: usize, end: usize },
+    /// An object whose closing brace is at `close`.
+    Obj {
+        close: usize,
+        entries: Vec<TextEntry>,
+    },
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Str(String),
+    Open,
+    Close,
+}
+
+/// Splits text VDF into tokens with their byte ranges, skipping comments and
+/// `[$CONDITION]` markers.
+fn tokenize(text: &str) -> Option<Vec<(Token, usize, usize)>> {
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b if b.is_ascii_whitespace() => i += 1,
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'{' => {
+                tokens.push((Token::Open, i, i + 1));
+                i += 1;
+            }
+            b'}' => {
+                tokens.push((Token::Close, i, i + 1));
+                i += 1;
+            }
+            b'"' => {
+                let start = i;
+                let mut value = String::new();
+                i += 1;
+                loop {
+                    match *bytes.get(i)? {
+                        b'"' => break,
+                        b'\\' => {
+                            // The escaped character may be more than one byte
+                            let ch = text.get(i + 1..)?.chars().next()?;
+                            value.push(match ch {
+                                'n' => '\n',
+                                't' => '\t',
+                                other => other,
+                            });
+                            i += 1 + ch.len_utf8();
+                        }
+                        _ => {
+                            let ch = text[i..].chars().next()?;
+                            value.push(ch);
+                            i += ch.len_utf8();
+                        }
+                    }
+                }
+                i += 1;
+                tokens.push((Token::Str(value), start, i));
+            }
+            b'[' => {
+                while i < bytes.len() && bytes[i] != b']' {
+                    i += 1;
+                }
+                i += 1;
+            }
+            _ => {
+                let start = i;
+                while i < bytes.len()
+                    && !bytes[i].is_ascii_whitespace()
+                    && !matches!(bytes[i], b'{' | b'}' | b'"')
+                {
+                    i += 1;
+                }
+                tokens.push((Token::Str(text[start..i].to_string()), start, i));
+            }
+        }
+    }
+    Some(tokens)
+}
+
+/// Parses entries up to the closing brace, returning its offset, or at the top
+/// level up to the end of the text.
+fn parse_entries(
+    tokens: &mut impl Iterator<Item = (Token, usize, usize)>,
+    top_level: bool,
+) -> Option<(Vec<TextEntry>, Option<usize>)> {
+    let mut entries = Vec::new();
+    loop {
+        let Some((token, start, _)) = tokens.next() else {
+            return top_level.then_some((entries, None));
+        };
+        let key = match token {
+            Token::Close if !top_level => return Some((entries, Some(start))),
+            Token::Str(key) => key,
+            _ => return None,
+        };
+        let value = match tokens.next()? {
+            (Token::Str(_), start, end) => TextValue::Str { start, end },
+            (Token::Open, _, _) => {
+                let (children, close) = parse_entries(tokens, false)?;
+                TextValue::Obj {
+                    close: close?,
+                    entries: children,
+                }
+            }
+            _ => return None,
+        };
+        entries.push(TextEntry { key, value });
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// The string `key` in the object at `path` of the text VDF `text`, with
+/// escapes resolved. `None` when it is missing or the text cannot be parsed.
+pub(crate) fn get_text_value(text: &str, path: &[&str], key: &str) -> Option<String> {
+    let mut tokens = tokenize(text)?.into_iter();
+    let (mut entries, _) = parse_entries(&mut tokens, true)?;
+    for name in path {
+        match entries
+            .into_iter()
+            .find(|entry| entry.key.eq_ignore_ascii_case(name))?
+            .value
+        {
+            TextValue::Obj {
+                entries: children, ..
+            } => entries = children,
+            TextValue::Str { .. } => return None,
+        }
+    }
+    match entries
+        .into_iter()
+        .find(|entry| entry.key.eq_ignore_ascii_case(key))?
+        .value
+    {
+        TextValue::Str { start, end } => match tokenize(&text[start..end])?.pop()? {
+            (Token::Str(value), _, _) => Some(value),
+            _ => None,
+        },
+        TextValue::Obj { .. } => None,
+    }
+}
+
+/// Sets `key` to `value` in the object at `path` of the text VDF `text`, creating
+/// the object and its missing parents. Everything else keeps its formatting.
+///
+/// With `replace` false an existing value is left alone. Returns `None` when
+/// the text cannot be parsed or a key on `path` holds a string.
+pub(crate) fn set_text_value(
+    text: &str,
+    path: &[&str],
+    key: &str,
+    value: &str,
+    replace: bool,
+) -> Option<String> {
+    let mut tokens = tokenize(text)?.into_iter();
+    let (mut entries, _) = parse_entries(&mut tokens, true)?;
+    // Where new entries go: before the closing brace of the deepest object found
+    let mut close = None;
+    let mut depth = 0;
+    for (i, name) in path.iter().enumerate() {
+        let found = entries
+            .into_iter()
+            .find(|entry| entry.key.eq_ignore_ascii_case(name));
+        match found.map(|entry| entry.value) {
+            Some(TextValue::Obj {
+                close: obj_close,
+                entries: children,
+            }) => {
+                close = Some(obj_close);
+                entries = children;
+                depth = i + 1;
+            }
+            Some(TextValue::Str { .. }) => return None,
+            None => return Some(insert(text, close, depth, &path[i..], key, value)),
+        }
+    }
+
+    match entries
+        .into_iter()
+        .find(|entry| entry.key.eq_ignore_ascii_case(key))
+        .map(|entry| entry.value)
+    {
+        Some(TextValue::Str { start, end }) if replace => Some(format!(
+            "{}\"{}\"{}",
+            &text[..start],
+            escape(value),
+            &text[end..]
+        )),
+        Some(TextValue::Str { .. }) => Some(text.to_string()),
+        Some(TextValue::Obj { .. }) => None,
+        None => Some(insert(text, close, depth, &[], key, value)),
+    }
+}
+
+/// Inserts `objects` nested inside each other, holding `key` and `value`, before
+/// the brace at `close` (or at the end of the text for the top level).
+fn insert(
+    text: &str,
+    close: Option<usize>,
+    depth: usize,
+    objects: &[&str],
+    key: &str,
+    value: &str,
+) -> String {
+    let indent = |level: usize| "\t".repeat(level);
+    let mut block = String::new();
+    for (i, name) in objects.iter().enumerate() {
+        let level = depth + i;
+        block += &format!(
+            "{}\"{}\"\n{}{{\n",
+            indent(level),
+            escape(name),
+            indent(level)
+        );
+    }
+    block += &format!(
+        "{}\"{}\"\t\t\"{}\"\n",
+        indent(depth + objects.len()),
+        escape(key),
+        escape(value)
+    );
+    for level in (depth..depth + objects.len()).rev() {
+        block += &format!("{}}}\n", indent(level));
+    }
+
+    let Some(close) = close else {
+        let separator = if text.is_empty() || text.ends_with('\n') {
+            ""
+        } else {
+            "\n"
+        };
+        return format!("{text}{separator}{block}");
+    };
+    // Put the block on its own lines when the brace starts its line
+    let line_start = text[..close].rfind('\n').map_or(0, |i| i + 1);
+    if text[line_start..close].trim().is_empty() {
+        format!("{}{block}{}", &text[..line_start], &text[line_start..])
+    } else {
+        format!("{}\n{block}{}", &text[..close], &text[close..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = "\"InstallConfigStore\"\n{\n\t\"Software\"\n\t{\n\t\t\"CompatToolMapping\"\n\t\t{\n\t\t\t\"100\"\n\t\t\t{\n\t\t\t\t\"name\"\t\t\"proton_8\"\n\t\t\t\t\"config\"\t\t\"\"\n\t\t\t}\n\t\t}\n\t}\n}\n";
+
+    #[test]
+    fn replaces_value_keeping_formatting() {
+        let path = ["InstallConfigStore", "Software", "CompatToolMapping", "100"];
+        let updated = set_text_value(CONFIG, &path, "NAME", "proton_9", true).unwrap();
+        assert_eq!(updated, CONFIG.replace("proton_8", "proton_9"));
+    }
+
+    #[test]
+    fn creates_missing_objects_at_their_depth() {
+        let path = ["InstallConfigStore", "Software", "CompatToolMapping", "200"];
+        let updated = set_text_value(CONFIG, &path, "name", "proton_9", true).unwrap();
+        let expected = CONFIG.replace(
+            "\t\t}\n\t}\n}",
+            "\t\t\t\"200\"\n\t\t\t{\n\t\t\t\t\"name\"\t\t\"proton_9\"\n\t\t\t}\n\t\t}\n\t}\n}",
+        );
+        assert_eq!(updated, expected);
+
+        let updated = set_text_value("", &["a", "b"], "key", "value", true).unwrap();
+        assert_eq!(
+            updated,
+            "\"a\"\n{\n\t\"b\"\n\t{\n\t\t\"key\"\t\t\"value\"\n\t}\n}\n"
+        );
+    }
+
+    #[test]
+    fn keeps_existing_value_without_replace() {
+        let path = ["InstallConfigStore", "Software", "CompatToolMapping", "100"];
+        let updated = set_text_value(CONFIG, &path, "name", "proton_9", false).unwrap();
+        assert_eq!(updated, CONFIG);
+
+        let updated = set_text_value(CONFIG, &path, "priority", "250", false).unwrap();
+        assert_eq!(
+            get_text_value(&updated, &path, "priority").as_deref(),
+            Some("250")
+        );
+    }
+
+    #[test]
+    fn refuses_string_on_path() {
+        let path = [
+            "InstallConfigStore",
+            "Software",
+            "CompatToolMapping",
+            "100",
+            "name",
+        ];
+        assert_eq!(set_text_value(CONFIG, &path, "key", "value", true), None);
+    }
+
+    #[test]
+    fn escapes_round_trip() {
+        let path = ["InstallConfigStore", "Software", "CompatToolMapping", "100"];
+        let value = r#"say "hi" C:\games\"#;
+        let updated = set_text_value(CONFIG, &path, "config", value, true).unwrap();
+        assert!(updated.contains(r#""say \"hi\" C:\\games\\""#));
+        assert_eq!(
+            get_text_value(&updated, &path, "config").as_deref(),
+            Some(value)
+        );
+    }
+
+    #[test]
+    fn escaped_multi_byte_character() {
+        let text = "\"apps\"\n{\n\t\"LaunchOptions\"\t\t\"-path C:\\éfoo %command%\"\n}\n";
+        assert_eq!(
+            get_text_value(text, &["apps"], "LaunchOptions").as_deref(),
+            Some("-path C:éfoo %command%")
+        );
+        let updated = set_text_value(text, &["apps"], "LaunchOptions", "é", true).unwrap();
+        assert_eq!(updated, "\"apps\"\n{\n\t\"LaunchOptions\"\t\t\"é\"\n}\n");
+    }
+
+    fn shortcuts() -> BinaryValue {
+        BinaryValue::Map(vec![(
+            "shortcuts".to_string(),
+            BinaryValue::Map(vec![(
+                "0".to_string(),
+                BinaryValue::Map(vec![
+                    ("appid".to_string(), BinaryValue::Int(2_786_274_309)),
+                    ("AppName".to_string(), BinaryValue::Str("Anki".to_string())),
+                    (
+                        "LastPlayTime".to_string(),
+                        BinaryValue::Long(1_710_000_000_123),
+                    ),
+                    ("Scale".to_string(), BinaryValue::Float(1.5)),
+                    ("tags".to_string(), BinaryValue::Map(Vec::new())),
+                ]),
+            )]),
+        )])
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let bytes = write_binary(&shortcuts());
+        assert_eq!(&bytes[..13], b"\x00shortcuts\x00\x000");
+        assert_eq!(parse_binary(&bytes), Some(shortcuts()));
+        assert_eq!(write_binary(&parse_binary(&bytes).unwrap()), bytes);
+    }
+
+    #[test]
+    fn rejects_truncated_binary() {
+        let bytes = write_binary(&shortcuts());
+        assert_eq!(parse_binary(&bytes[..bytes.len() - 1]), None);
+        assert_eq!(parse_binary(b"\x09key\x00"), None);
+    }
+
+    #[test]
+    fn binary_set_adds_or_replaces() {
+        let mut shortcut = shortcuts().entries()[0].1.entries()[0].1.clone();
+        shortcut.set("appname", BinaryValue::Str("Anki 2".to_string()));
+        shortcut.set("LaunchOptions", BinaryValue::Str("-v".to_string()));
+        let keys: Vec<&str> = shortcut.entries().iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(
+            keys,
+            [
+                "appid",
+                "AppName",
+                "LastPlayTime",
+                "Scale",
+                "tags",
+                "LaunchOptions"
+            ]
+        );
+        assert_eq!(
+            shortcut.get("AppName").and_then(BinaryValue::as_str),
+            Some("Anki 2")
+        );
+        assert_eq!(
+            shortcut.get("launchoptions").and_then(BinaryValue::as_str),
+            Some("-v")
+        );
+    }
+}